@@ -0,0 +1,93 @@
+#![allow(clippy::needless_return)]
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use zombie_zone_core::{Entity, Lane, SpawnCurve, State, Turret, TurretKind, Zombie, LOCAL_PLAYER};
+
+/// every lane open, a turret on each of the first fields and a zombie every tick
+fn full_board() -> State {
+    let mut state = State::new();
+    for i in 0..state.grid.len() {
+        let mut lane = Lane::default();
+        for field in lane.iter_mut().take(3) {
+            field.push_back(Entity::Turret(Turret::new(TurretKind::Rapid, LOCAL_PLAYER)));
+        }
+        state.grid.set_lane(i, Some(lane));
+        state.set_spawn_curve(i, SpawnCurve::Constant(1));
+    }
+    return state;
+}
+
+/// every lane open but only the middle one in play
+fn quiet_board() -> State {
+    let mut state = State::default();
+    for i in 0..state.grid.len() {
+        if state.grid[i].is_none() {
+            state.grid.set_lane(i, Some(Lane::default()));
+            state.set_spawn_curve(i, SpawnCurve::Off);
+        }
+    }
+    return state;
+}
+
+/// every lane open and every free field holding a tall stack of zombies
+fn crowded_board() -> State {
+    let mut state = full_board();
+    for i in 0..state.grid.len() {
+        for field in state.grid.lane_mut(i).unwrap().iter_mut().skip(4) {
+            for _ in 0..20 {
+                field.push_back(Entity::Zombie(Zombie::new(0, 200)));
+            }
+        }
+    }
+    return state;
+}
+
+fn bench_ticks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tick");
+
+    group.bench_function("default board, 1 tick", |b| {
+        b.iter_batched_ref(State::default, |state| state.next(), BatchSize::SmallInput)
+    });
+    group.bench_function("full board, 1000 ticks", |b| {
+        b.iter_batched_ref(
+            full_board,
+            |state| {
+                for _ in 0..1000 {
+                    state.next();
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("quiet board, 1000 ticks keeping every state", |b| {
+        b.iter_batched_ref(
+            quiet_board,
+            |state| {
+                let mut history = Vec::with_capacity(1000);
+                for _ in 0..1000 {
+                    history.push(state.clone());
+                    state.next();
+                }
+                black_box(history.len());
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("crowded board, 100 ticks", |b| {
+        b.iter_batched_ref(
+            crowded_board,
+            |state| {
+                for _ in 0..100 {
+                    state.next();
+                }
+                black_box(state.tick);
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_ticks);
+criterion_main!(benches);
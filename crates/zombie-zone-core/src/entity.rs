@@ -0,0 +1,357 @@
+//! Everything that can stand on a field. Each kind of entity lives in its own
+//! module and implements `EntityBehavior`, which covers its tick, how it
+//! interacts with the others, how it is drawn, hashed and written as text.
+//! The lane only hands it a `TickContext` and never looks at what kind it is.
+//!
+//! Adding a kind takes its module, its type in the `entities!` list below
+//! (which keeps states comparable and serializable) and an entry in
+//! `Registry::builtin` (which makes it spawnable and readable as text).
+
+use crate::{Attributes, Damage, EffectKind, Field, Lane, ParseBoardError, StableHash, Tick, LOCAL_PLAYER};
+
+mod bullet;
+mod turret;
+mod zombie;
+
+pub use bullet::*;
+pub use turret::*;
+pub use zombie::*;
+
+/// Declares `Entity` with a variant per kind, named after the kind's type,
+/// and hands every call on an `Entity` to the kind's `EntityBehavior`. The
+/// number in front of a kind is hashed ahead of its entities, it must never
+/// change or be reused.
+macro_rules! entities {
+    ($($tag:literal => $kind:ident),* $(,)?) => {
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub enum Entity {
+            $($kind($kind),)*
+        }
+
+        impl Entity {
+            pub fn behavior(&self) -> &dyn EntityBehavior {
+                match self {
+                    $(Entity::$kind(entity) => entity,)*
+                }
+            }
+
+            pub fn behavior_mut(&mut self) -> &mut dyn EntityBehavior {
+                match self {
+                    $(Entity::$kind(entity) => entity,)*
+                }
+            }
+
+            pub fn tick(self, ctx: &mut TickContext) -> Option<Entity> {
+                match self {
+                    $(Entity::$kind(entity) => entity.tick(ctx),)*
+                }
+            }
+
+            /// tells the kinds apart in the state hash
+            pub(crate) fn tag(&self) -> u8 {
+                match self {
+                    $(Entity::$kind(_) => $tag,)*
+                }
+            }
+        }
+
+        $(
+            impl From<$kind> for Entity {
+                fn from(entity: $kind) -> Entity {
+                    Entity::$kind(entity)
+                }
+            }
+        )*
+    };
+}
+
+entities! {
+    0 => Zombie,
+    1 => Turret,
+    2 => Bullet,
+}
+
+/// how the other entities of a field treat an entity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// walks towards the players, tramples defenders and gets hit by projectiles
+    Attacker,
+    /// at most one per field, gets trampled by an attacker entering its field
+    Defender,
+    /// used up when an attacker walks into it
+    Projectile,
+}
+
+/// how an entity is drawn, the ui picks the actual colours for a style
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Glyph {
+    pub symbol: &'static str,
+    pub style: GlyphStyle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlyphStyle {
+    Plain,
+    /// busy attacking
+    Alert,
+    /// held up
+    Muted,
+    /// stunned
+    Cold,
+    /// out of the game
+    Faded,
+}
+
+impl Glyph {
+    pub fn new(symbol: &'static str, style: GlyphStyle) -> Glyph {
+        Glyph { symbol, style }
+    }
+}
+
+/// `StableHash` writes everything that sets one entity of the kind apart
+/// from another, `State::hash` depends on it.
+pub trait EntityBehavior: StableHash {
+    /// the name it is registered under
+    fn kind(&self) -> &'static str;
+
+    fn role(&self) -> Role;
+
+    fn glyph(&self) -> Glyph;
+
+    /// which way the entity is about to move along the lane, -1 for left and 1 for right
+    fn heading(&self) -> i8 {
+        0
+    }
+
+    /// whether turrets fire at it and bullets hit it
+    fn is_target(&self) -> bool {
+        false
+    }
+
+    /// whether it holds up walking zombies that want to enter its field
+    fn is_obstacle(&self, _current_tick: Tick) -> bool {
+        false
+    }
+
+    /// damage dealt to an attacker walking into it
+    fn contact_damage(&self) -> Option<Damage> {
+        None
+    }
+
+    /// only called on targets
+    fn take_hit(&mut self, _damage: Damage, _current_tick: Tick, _effects: &mut Vec<EffectKind>) {}
+
+    /// what sets it apart from a freshly spawned one in the text format,
+    /// `key=value` or lone `key`, read back by `EntityKind::parse`
+    fn text_attributes(&self) -> Vec<String> {
+        vec![]
+    }
+
+    /// returns the entity if it stays on its field, it has been taken off
+    /// the field for the call
+    fn tick(self, ctx: &mut TickContext) -> Option<Entity>
+    where
+        Self: Sized;
+}
+
+/// The lane around an entity during its tick.
+pub struct TickContext<'a> {
+    current_tick: Tick,
+    lane: &'a mut Lane,
+    i_field: usize,
+    effects: &'a mut Vec<EffectKind>,
+    /// effects produced during a tick belong to the neighbour field the entity affected
+    i_affected: usize,
+}
+
+/// a field next to the ticking entity, effects pushed here get drawn on it
+pub struct Neighbour<'a> {
+    pub field: &'a mut Field,
+    pub effects: &'a mut Vec<EffectKind>,
+}
+
+impl<'a> TickContext<'a> {
+    pub(crate) fn new(
+        current_tick: Tick,
+        lane: &'a mut Lane,
+        i_field: usize,
+        effects: &'a mut Vec<EffectKind>,
+    ) -> TickContext<'a> {
+        TickContext {
+            current_tick,
+            lane,
+            i_field,
+            effects,
+            i_affected: i_field,
+        }
+    }
+
+    pub fn current_tick(&self) -> Tick {
+        self.current_tick
+    }
+
+    /// the field `offset` fields to the right, `None` past either end of the lane
+    pub fn neighbour(&mut self, offset: isize) -> Option<Neighbour<'_>> {
+        let i = self.i_field.checked_add_signed(offset)?;
+        if i >= self.lane.len() {
+            return None;
+        }
+
+        self.i_affected = i;
+        return Some(Neighbour {
+            field: &mut self.lane[i],
+            effects: self.effects,
+        });
+    }
+
+    /// whether a target stands on one of the `range` fields right of the entity
+    pub fn target_ahead(&self, range: usize) -> bool {
+        self.lane.iter().skip(self.i_field + 1).take(range).any(Field::has_zombie)
+    }
+
+    pub(crate) fn i_affected(&self) -> usize {
+        self.i_affected
+    }
+}
+
+/// a kind of entity as listed for players, editors and scripts
+#[derive(Debug, Clone)]
+pub struct EntityKind {
+    /// same as `EntityBehavior::kind` of the entities it spawns
+    pub name: &'static str,
+    pub description: &'static str,
+    pub role: Role,
+    /// a fresh entity of this kind, placed at the given tick
+    pub spawn: fn(Tick) -> Entity,
+    /// what the text format writes for it, unique among the kinds
+    pub symbol: char,
+    /// an entity of this kind from its attributes in the text format, which
+    /// default to those of an entity spawned at tick 0
+    pub parse: fn(&mut Attributes) -> Result<Entity, ParseBoardError>,
+}
+
+/// the known kinds of entities, in registration order
+#[derive(Debug, Clone)]
+pub struct Registry {
+    kinds: Vec<EntityKind>,
+}
+
+impl Registry {
+    pub fn empty() -> Registry {
+        Registry { kinds: vec![] }
+    }
+
+    pub fn builtin() -> Registry {
+        let mut registry = Registry::empty();
+        registry.register(EntityKind {
+            name: "zombie",
+            description: "walks left, attacks turrets and walks over them",
+            role: Role::Attacker,
+            spawn: |tick| Entity::Zombie(Zombie::new(tick, ZOMBIE_HEALTH)),
+            symbol: 'Z',
+            parse: |attributes| Ok(Entity::Zombie(Zombie::from_attributes(attributes)?)),
+        });
+        registry.register(EntityKind {
+            name: "turret",
+            description: "shoots at zombies to its right",
+            role: Role::Defender,
+            spawn: |_| Entity::Turret(Turret::new(TurretKind::Basic, LOCAL_PLAYER)),
+            symbol: 'T',
+            parse: |attributes| Ok(Entity::Turret(Turret::from_attributes(attributes)?)),
+        });
+        registry.register(EntityKind {
+            name: "bullet",
+            description: "flies right until it hits a zombie",
+            role: Role::Projectile,
+            spawn: |tick| Entity::Bullet(Bullet(tick, TURRET_TIERS[0].damage)),
+            symbol: '°',
+            parse: |attributes| Ok(Entity::Bullet(Bullet::from_attributes(attributes)?)),
+        });
+        return registry;
+    }
+
+    /// a kind with the name of a registered one replaces it
+    pub fn register(&mut self, kind: EntityKind) {
+        match self.kinds.iter_mut().find(|known| known.name == kind.name) {
+            Some(known) => *known = kind,
+            None => self.kinds.push(kind),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&EntityKind> {
+        self.kinds.iter().find(|kind| kind.name == name)
+    }
+
+    /// the kind the text format writes as `symbol`
+    pub fn by_symbol(&self, symbol: char) -> Option<&EntityKind> {
+        self.kinds.iter().find(|kind| kind.symbol == symbol)
+    }
+
+    pub fn spawn(&self, name: &str, tick: Tick) -> Option<Entity> {
+        self.get(name).map(|kind| (kind.spawn)(tick))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &EntityKind> {
+        self.kinds.iter()
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Registry {
+        Registry::builtin()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_kinds_spawn_matching_entities() {
+        let registry = Registry::builtin();
+        let names: Vec<&str> = registry.iter().map(|kind| kind.name).collect();
+        assert_eq!(vec!["zombie", "turret", "bullet"], names);
+
+        for kind in registry.iter() {
+            let entity = (kind.spawn)(3);
+            assert_eq!(kind.name, entity.behavior().kind());
+            assert_eq!(kind.role, entity.behavior().role());
+
+            let fresh = (kind.spawn)(0);
+            assert_eq!(kind.symbol.to_string(), fresh.to_string());
+            assert_eq!(Some(kind.name), registry.by_symbol(kind.symbol).map(|known| known.name));
+        }
+        assert!(registry.spawn("ghost", 3).is_none());
+    }
+
+    #[test]
+    fn registering_a_known_name_replaces_the_kind() {
+        let mut registry = Registry::builtin();
+        registry.register(EntityKind {
+            name: "zombie",
+            description: "a tough one",
+            role: Role::Attacker,
+            spawn: |tick| Entity::Zombie(Zombie::new(tick, 10)),
+            symbol: 'Z',
+            parse: |attributes| Ok(Entity::Zombie(Zombie::from_attributes(attributes)?)),
+        });
+
+        assert_eq!(3, registry.iter().count());
+        assert_eq!(Some(Entity::Zombie(Zombie::new(1, 10))), registry.spawn("zombie", 1));
+    }
+
+    #[test]
+    fn glyph_and_heading_follow_the_behaviour() {
+        let mut zombie = Zombie::new(0, ZOMBIE_HEALTH);
+        assert_eq!(Glyph { symbol: "Z", style: GlyphStyle::Plain }, zombie.glyph());
+        assert_eq!(-1, zombie.heading());
+
+        zombie.behaviour = Behaviour::Blocked;
+        assert_eq!(GlyphStyle::Muted, zombie.glyph().style);
+        assert_eq!(0, zombie.heading());
+
+        assert_eq!(1, Bullet(0, 1).heading());
+        assert_eq!(0, Turret::new(TurretKind::Basic, LOCAL_PLAYER).heading());
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{
+    Attributes, Damage, Entity, EntityBehavior, Glyph, GlyphStyle, ParseBoardError, Role, StableHash, StableHasher, Tick,
+    TickContext, TURRET_TIERS,
+};
+
+/// flies one field to the right per tick and hits the first zombie it meets
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bullet(pub Tick, pub Damage);
+
+impl Bullet {
+    pub fn last_moved(&self) -> Tick {
+        self.0
+    }
+
+    pub fn damage(&self) -> Damage {
+        self.1
+    }
+
+    /// `t` last moved, `dmg`
+    pub fn from_attributes(attributes: &mut Attributes) -> Result<Bullet, ParseBoardError> {
+        return Ok(Bullet(
+            attributes.number("t")?.unwrap_or(0),
+            attributes.number("dmg")?.unwrap_or(TURRET_TIERS[0].damage),
+        ));
+    }
+}
+
+impl EntityBehavior for Bullet {
+    fn kind(&self) -> &'static str {
+        "bullet"
+    }
+
+    fn role(&self) -> Role {
+        Role::Projectile
+    }
+
+    fn glyph(&self) -> Glyph {
+        Glyph::new("°", GlyphStyle::Plain)
+    }
+
+    fn heading(&self) -> i8 {
+        1
+    }
+
+    fn contact_damage(&self) -> Option<Damage> {
+        Some(self.damage())
+    }
+
+    fn text_attributes(&self) -> Vec<String> {
+        let mut attributes = vec![];
+        if self.last_moved() != 0 {
+            attributes.push(format!("t={}", self.last_moved()));
+        }
+        if self.damage() != TURRET_TIERS[0].damage {
+            attributes.push(format!("dmg={}", self.damage()));
+        }
+        return attributes;
+    }
+
+    /// a moving bullet flies into the field to its right
+    fn tick(self, ctx: &mut TickContext) -> Option<Entity> {
+        let current_tick = ctx.current_tick();
+        if self.last_moved() == current_tick {
+            return Some(Entity::Bullet(self));
+        }
+
+        match ctx.neighbour(1) {
+            Some(next) => {
+                if next.field.has_zombie() {
+                    /* hit zombie */
+                    next.field.hit_target(self.damage(), current_tick, next.effects);
+                    return None;
+                }
+
+                /* empty, other bullets or a turret to fly over */
+                next.field.push_back(Entity::Bullet(Bullet(current_tick, self.damage())));
+                return None;
+            }
+            None => {
+                return None;
+            }
+        }
+    }
+}
+
+impl StableHash for Bullet {
+    fn stable_hash(&self, hasher: &mut StableHasher) {
+        hasher.write_u32(self.last_moved());
+        hasher.write_u8(self.damage());
+    }
+}
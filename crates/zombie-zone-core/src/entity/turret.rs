@@ -0,0 +1,181 @@
+use crate::{
+    Attributes, Bullet, Damage, EffectKind, Entity, EntityBehavior, Glyph, GlyphStyle, Money, ParseBoardError, PlayerId,
+    Role, StableHash, StableHasher, Tick, TickContext, LOCAL_PLAYER,
+};
+
+/// percentage of everything invested into a turret (placement + upgrades) paid back on sell
+pub const SELL_REFUND_PERCENT: Money = 50;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Turret {
+    pub kind: TurretKind,
+    /// index into `TURRET_TIERS`
+    pub tier: usize,
+    pub owner: PlayerId,
+    /// ticks left until the turret can fire again
+    pub cooldown: Tick,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TurretKind {
+    Basic,
+    Rapid,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurretTier {
+    /// price to reach this tier, for tier 0 this is the placement price
+    pub cost: Money,
+    pub damage: Damage,
+    /// how many fields ahead of the turret a zombie gets engaged
+    pub range: usize,
+}
+
+pub const TURRET_TIERS: [TurretTier; 3] = [
+    TurretTier { cost: 20, damage: 1, range: 5 },
+    TurretTier { cost: 30, damage: 2, range: 6 },
+    TurretTier { cost: 50, damage: 3, range: 8 },
+];
+
+impl Turret {
+    /// a freshly placed turret has to warm up before its first shot
+    pub fn new(kind: TurretKind, owner: PlayerId) -> Turret {
+        Turret {
+            kind,
+            tier: 0,
+            owner,
+            cooldown: kind.warm_up_ticks(),
+        }
+    }
+
+    pub fn stats(&self) -> &TurretTier {
+        &TURRET_TIERS[self.tier]
+    }
+
+    /// `None` once the turret reached the highest tier
+    pub fn upgrade_cost(&self) -> Option<Money> {
+        TURRET_TIERS.get(self.tier + 1).map(|tier| tier.cost)
+    }
+
+    pub fn refund(&self) -> Money {
+        let invested: Money = TURRET_TIERS[..=self.tier].iter().map(|tier| tier.cost).sum();
+        invested * SELL_REFUND_PERCENT / 100
+    }
+
+    /// `basic` or `rapid`, `tier`, `owner`, `cd` cooldown
+    pub fn from_attributes(attributes: &mut Attributes) -> Result<Turret, ParseBoardError> {
+        let kind = match (attributes.flag("basic")?, attributes.flag("rapid")?) {
+            (true, true) => return Err(ParseBoardError::BadAttribute(String::from("rapid"))),
+            (_, true) => TurretKind::Rapid,
+            _ => TurretKind::Basic,
+        };
+        let mut turret = Turret::new(kind, attributes.number("owner")?.unwrap_or(LOCAL_PLAYER));
+        if let Some(tier) = attributes.number("tier")? {
+            if tier >= TURRET_TIERS.len() {
+                return Err(ParseBoardError::BadValue(format!("tier={}", tier)));
+            }
+            turret.tier = tier;
+        }
+        if let Some(cooldown) = attributes.number("cd")? {
+            turret.cooldown = cooldown;
+        }
+        return Ok(turret);
+    }
+}
+
+impl TurretKind {
+    /// must be at least 1
+    pub fn fire_every_x_ticks(&self) -> Tick {
+        match self {
+            TurretKind::Basic => 2,
+            TurretKind::Rapid => 1,
+        }
+    }
+
+    pub fn warm_up_ticks(&self) -> Tick {
+        match self {
+            TurretKind::Basic => 2,
+            TurretKind::Rapid => 3,
+        }
+    }
+}
+
+impl EntityBehavior for Turret {
+    fn kind(&self) -> &'static str {
+        "turret"
+    }
+
+    fn role(&self) -> Role {
+        Role::Defender
+    }
+
+    fn glyph(&self) -> Glyph {
+        Glyph::new("T", GlyphStyle::Plain)
+    }
+
+    fn text_attributes(&self) -> Vec<String> {
+        let mut attributes = vec![];
+        if self.kind == TurretKind::Rapid {
+            attributes.push(String::from("rapid"));
+        }
+        if self.tier != 0 {
+            attributes.push(format!("tier={}", self.tier));
+        }
+        if self.owner != LOCAL_PLAYER {
+            attributes.push(format!("owner={}", self.owner));
+        }
+        if self.cooldown != self.kind.warm_up_ticks() {
+            attributes.push(format!("cd={}", self.cooldown));
+        }
+        return attributes;
+    }
+
+    /// fires into the field to its right once a zombie is in range, a turret always stays
+    fn tick(mut self, ctx: &mut TickContext) -> Option<Entity> {
+        if self.cooldown > 0 {
+            self.cooldown -= 1;
+            return Some(Entity::Turret(self));
+        }
+
+        if !ctx.target_ahead(self.stats().range) {
+            /* stay loaded until a zombie comes close enough */
+            return Some(Entity::Turret(self));
+        }
+
+        self.cooldown = self.kind.fire_every_x_ticks() - 1;
+        let damage = self.stats().damage;
+        let current_tick = ctx.current_tick();
+
+        match ctx.neighbour(1) {
+            None => {
+                /* end of lane, do nothing? */
+                return Some(Entity::Turret(self));
+            }
+            Some(next) => {
+                next.effects.push(EffectKind::MuzzleFlash);
+
+                if next.field.has_zombie() {
+                    next.field.hit_target(damage, current_tick, next.effects);
+                    return Some(Entity::Turret(self));
+                }
+
+                next.field.push_back(Entity::Bullet(Bullet(current_tick, damage)));
+                return Some(Entity::Turret(self));
+            }
+        }
+    }
+}
+
+impl StableHash for Turret {
+    fn stable_hash(&self, hasher: &mut StableHasher) {
+        hasher.write_u8(match self.kind {
+            TurretKind::Basic => 0,
+            TurretKind::Rapid => 1,
+        });
+        hasher.write_usize(self.tier);
+        hasher.write_u8(self.owner);
+        hasher.write_u32(self.cooldown);
+    }
+}
@@ -0,0 +1,242 @@
+use crate::{
+    Attributes, Damage, EffectKind, Entity, EntityBehavior, Field, Glyph, GlyphStyle, Health, ParseBoardError, Role,
+    StableHash, StableHasher, Tick, TickContext,
+};
+
+pub const ZOMBIE_HEALTH: Health = 1;
+/// ticks from the first bite until a zombie has chewed through a turret
+pub const ATTACK_TICKS: Tick = 2;
+/// ticks from a hit until the zombie walks again
+pub const STUN_TICKS: Tick = 2;
+/// ticks a killed zombie stays on its field
+pub const DYING_TICKS: Tick = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Zombie {
+    pub last_moved: Tick,
+    pub health: Health,
+    pub behaviour: Behaviour,
+}
+
+/// Every tick a zombie first finishes a timed behaviour (stunned, dying),
+/// then looks at the field ahead:
+/// - a turret makes it attack, once the attack is over it tramples the turret
+/// - a zombie ahead that did not walk this tick blocks it
+/// - otherwise it walks, bullets in the entered field can stun or kill it
+///
+/// All `until` values are ticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Behaviour {
+    Walking,
+    Attacking { until: Tick },
+    Blocked,
+    Stunned { until: Tick },
+    /// out of the game, stays on its field until it gets removed
+    Dying { until: Tick },
+}
+
+impl Zombie {
+    pub fn new(last_moved: Tick, health: Health) -> Zombie {
+        Zombie {
+            last_moved,
+            health,
+            behaviour: Behaviour::Walking,
+        }
+    }
+
+    pub fn is_dying(&self) -> bool {
+        matches!(self.behaviour, Behaviour::Dying { .. })
+    }
+
+    /// `t` last moved, `hp`, at most one of `attacking`, `blocked`, `stunned`, `dying`
+    pub fn from_attributes(attributes: &mut Attributes) -> Result<Zombie, ParseBoardError> {
+        let mut zombie = Zombie::new(attributes.number("t")?.unwrap_or(0), attributes.number("hp")?.unwrap_or(ZOMBIE_HEALTH));
+
+        let behaviours = [
+            ("attacking", attributes.number("attacking")?.map(|until| Behaviour::Attacking { until })),
+            ("blocked", attributes.flag("blocked")?.then_some(Behaviour::Blocked)),
+            ("stunned", attributes.number("stunned")?.map(|until| Behaviour::Stunned { until })),
+            ("dying", attributes.number("dying")?.map(|until| Behaviour::Dying { until })),
+        ];
+        let mut behaviours = behaviours.into_iter().filter_map(|(key, behaviour)| Some((key, behaviour?)));
+        zombie.behaviour = behaviours.next().map_or(Behaviour::Walking, |(_, behaviour)| behaviour);
+        if let Some((key, _)) = behaviours.next() {
+            return Err(ParseBoardError::BadAttribute(String::from(key)));
+        }
+        return Ok(zombie);
+    }
+
+    /// the zombie steps onto `field`: every projectile waiting there hits it and a defender gets trampled
+    pub(crate) fn enter(mut self, field: &mut Field, current_tick: Tick, effects: &mut Vec<EffectKind>) {
+        self.last_moved = current_tick;
+
+        while let Some(i_projectile) = field.iter().position(|entity| entity.behavior().contact_damage().is_some()) {
+            let damage = field.remove(i_projectile).and_then(|entity| entity.behavior().contact_damage());
+
+            self.take_hit(damage.unwrap_or(0), current_tick, effects);
+            if self.is_dying() {
+                /* zombie walks into the bullet */
+                field.push_back(Entity::Zombie(self));
+                return;
+            }
+            /* zombie walks into the bullet and survives it */
+        }
+
+        if field.has_turret() {
+            /* zombie walks over turret */
+            effects.push(EffectKind::Explosion);
+            field.retain(|entity| entity.behavior().role() != Role::Defender);
+        }
+
+        field.push_back(Entity::Zombie(self));
+    }
+}
+
+impl EntityBehavior for Zombie {
+    fn kind(&self) -> &'static str {
+        "zombie"
+    }
+
+    fn role(&self) -> Role {
+        Role::Attacker
+    }
+
+    fn glyph(&self) -> Glyph {
+        match self.behaviour {
+            Behaviour::Walking => Glyph::new("Z", GlyphStyle::Plain),
+            Behaviour::Attacking { .. } => Glyph::new("Z", GlyphStyle::Alert),
+            Behaviour::Blocked => Glyph::new("Z", GlyphStyle::Muted),
+            Behaviour::Stunned { .. } => Glyph::new("z", GlyphStyle::Cold),
+            Behaviour::Dying { .. } => Glyph::new("z", GlyphStyle::Faded),
+        }
+    }
+
+    fn heading(&self) -> i8 {
+        if self.behaviour == Behaviour::Walking {
+            return -1;
+        }
+        return 0;
+    }
+
+    /// a dying zombie is out of the game
+    fn is_target(&self) -> bool {
+        !self.is_dying()
+    }
+
+    /// a zombie that did not walk this tick holds up the ones behind it
+    fn is_obstacle(&self, current_tick: Tick) -> bool {
+        match self.behaviour {
+            Behaviour::Dying { .. } => false,
+            Behaviour::Walking => self.last_moved != current_tick,
+            _ => true,
+        }
+    }
+
+    /// a surviving zombie gets stunned, a killed one starts dying
+    fn take_hit(&mut self, damage: Damage, current_tick: Tick, effects: &mut Vec<EffectKind>) {
+        if self.health > damage {
+            self.health -= damage;
+            self.behaviour = Behaviour::Stunned {
+                until: current_tick + STUN_TICKS,
+            };
+            effects.push(EffectKind::HitSpark);
+        } else {
+            self.health = 0;
+            self.behaviour = Behaviour::Dying {
+                until: current_tick + DYING_TICKS,
+            };
+            effects.push(EffectKind::DeathPuff);
+        }
+    }
+
+    fn text_attributes(&self) -> Vec<String> {
+        let mut attributes = vec![];
+        if self.last_moved != 0 {
+            attributes.push(format!("t={}", self.last_moved));
+        }
+        if self.health != ZOMBIE_HEALTH {
+            attributes.push(format!("hp={}", self.health));
+        }
+        match self.behaviour {
+            Behaviour::Walking => {}
+            Behaviour::Attacking { until } => attributes.push(format!("attacking={}", until)),
+            Behaviour::Blocked => attributes.push(String::from("blocked")),
+            Behaviour::Stunned { until } => attributes.push(format!("stunned={}", until)),
+            Behaviour::Dying { until } => attributes.push(format!("dying={}", until)),
+        }
+        return attributes;
+    }
+
+    /// a walking zombie enters the field to its left
+    fn tick(mut self, ctx: &mut TickContext) -> Option<Entity> {
+        let current_tick = ctx.current_tick();
+
+        match self.behaviour {
+            Behaviour::Dying { until } if current_tick >= until => {
+                return None;
+            }
+            Behaviour::Dying { .. } => {
+                return Some(Entity::Zombie(self));
+            }
+            Behaviour::Stunned { until } if current_tick < until => {
+                return Some(Entity::Zombie(self));
+            }
+            _ => {}
+        }
+
+        let prev = match ctx.neighbour(-1) {
+            None => {
+                /* prev is end of lane */
+                //todo:
+                //end of lane, hit player
+                return None;
+            }
+            Some(prev) => prev,
+        };
+
+        if prev.field.has_turret() {
+            let until = match self.behaviour {
+                Behaviour::Attacking { until } => until,
+                _ => current_tick + ATTACK_TICKS,
+            };
+
+            if current_tick < until {
+                self.behaviour = Behaviour::Attacking { until };
+                return Some(Entity::Zombie(self));
+            }
+            /* the turret is done for, walk over it */
+        } else if prev.field.blocks_zombies(current_tick) {
+            self.behaviour = Behaviour::Blocked;
+            return Some(Entity::Zombie(self));
+        }
+
+        self.behaviour = Behaviour::Walking;
+        self.enter(prev.field, current_tick, prev.effects);
+        return None;
+    }
+}
+
+impl StableHash for Zombie {
+    fn stable_hash(&self, hasher: &mut StableHasher) {
+        hasher.write_u32(self.last_moved);
+        hasher.write_u8(self.health);
+        match self.behaviour {
+            Behaviour::Walking => hasher.write_u8(0),
+            Behaviour::Attacking { until } => {
+                hasher.write_u8(1);
+                hasher.write_u32(until);
+            }
+            Behaviour::Blocked => hasher.write_u8(2),
+            Behaviour::Stunned { until } => {
+                hasher.write_u8(3);
+                hasher.write_u32(until);
+            }
+            Behaviour::Dying { until } => {
+                hasher.write_u8(4);
+                hasher.write_u32(until);
+            }
+        }
+    }
+}
@@ -0,0 +1,840 @@
+use std::{collections::VecDeque, rc::Rc};
+
+use crate::{Entity, Role, TickContext, Turret, TurretKind, Zombie, TURRET_TIERS, ZOMBIE_HEALTH};
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct State {
+    pub(crate) tick_interval_ms: u16,
+    pub tick: Tick,
+    pub grid: Grid,
+    pub money: Money,
+    pub effects: Vec<Effect>,
+    pub(crate) spawn_rates: SpawnRates,
+}
+
+pub type Tick = u32;
+pub type Money = u32;
+pub type PlayerId = u8;
+pub type Health = u8;
+pub type Damage = Health;
+
+pub const LOCAL_PLAYER: PlayerId = 0;
+pub const START_MONEY: Money = 100;
+
+/// Lanes sit behind `Rc`, so a cloned state shares every lane with the
+/// original until one of them changes it. A tick only copies the lanes it
+/// changes and lanes without entities are left alone, so keeping earlier
+/// states around costs little.
+#[derive(Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Grid([Option<Rc<Lane>>; 5]);
+
+#[derive(Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lane([Field; 9]);
+
+/// A field holds a stack of entities and every one of them is processed each
+/// tick, in stack order. What can share a field:
+/// - zombies stack with zombies and bullets with bullets
+/// - bullets fly over turrets, so a bullet can share the field of a turret
+/// - there is at most one turret per field
+/// - zombies never share a field with bullets or a turret, a zombie entering
+///   a field gets hit by the bullets in it and tramples the turret
+/// - dying zombies are out of the game and share a field with anything
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Field(pub VecDeque<Entity>);
+
+/// Purely visual, effects are drawn on top of a field for a few ticks and
+/// never take part in the game rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Effect {
+    pub kind: EffectKind,
+    pub lane: usize,
+    pub field: usize,
+    /// the effect is gone once this reaches 0
+    pub ticks_left: Tick,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EffectKind {
+    /// a turret got trampled by a zombie
+    Explosion,
+    /// a zombie got killed
+    DeathPuff,
+    /// a turret fired into this field
+    MuzzleFlash,
+    /// a zombie got hit and survived
+    HitSpark,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionError {
+    NoLane,
+    NoTurret,
+    NotOwner,
+    MaxTier,
+    InsufficientFunds,
+    NoField,
+    /// the entity may not share the field with what already stands on it
+    Occupied,
+}
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct SpawnRates {
+    /// one curve per lane, indexed like the grid
+    pub(crate) zombies: [SpawnCurve; 5],
+    /// tick of the last zombie spawn per lane
+    pub(crate) last_zombie_spawns: [Tick; 5],
+}
+
+/// spawn interval of a lane over the course of a level,
+/// every interval is given in ticks and an interval of 0 never spawns
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SpawnCurve {
+    Off,
+    Constant(Tick),
+    /// moves linearly from `from` to `to` within the first `over_ticks` ticks and stays at `to` afterwards
+    LinearRamp { from: Tick, to: Tick, over_ticks: Tick },
+    /// `before` until tick `at`, `after` from then on
+    Step { before: Tick, after: Tick, at: Tick },
+    /// swings between `min` and `max` and back within `period` ticks, starting at the middle
+    Sine { min: Tick, max: Tick, period: Tick },
+}
+
+/// sin(k * 90° / 16) * 1000 for k in 0..=16, keeps the sine curve free of floats
+/// so every platform computes the same intervals
+const QUARTER_SINE_PERMILLE: [i64; 17] = [
+    0, 98, 195, 290, 383, 471, 556, 634, 707, 773, 831, 882, 924, 957, 981, 995, 1000,
+];
+
+// inital state
+impl std::default::Default for State {
+    fn default() -> Self {
+        // State::new()
+        State {
+            tick: 0,
+            tick_interval_ms: 700,
+            money: START_MONEY,
+            effects: vec![],
+            spawn_rates: SpawnRates::uniform(SpawnCurve::Constant(1)),
+            grid: Grid([
+                None,
+                None,
+                Some(Rc::new(Lane([
+                    Field(VecDeque::from([Entity::Turret(Turret::new(TurretKind::Basic, LOCAL_PLAYER))])),
+                    Field(VecDeque::new()),
+                    Field(VecDeque::new()),
+                    Field(VecDeque::new()),
+                    Field(VecDeque::new()),
+                    Field(VecDeque::new()),
+                    Field(VecDeque::new()),
+                    // Field(VecDeque::new()),
+                    // Field(VecDeque::new()),
+                    Field(VecDeque::from([Entity::Zombie(Zombie::new(0, ZOMBIE_HEALTH))])),
+                    Field(VecDeque::from([Entity::Zombie(Zombie::new(0, ZOMBIE_HEALTH))])),
+                ]))),
+                None,
+                None,
+            ]),
+        }
+    }
+}
+
+impl State {
+    pub fn new() -> State {
+        let mut state = State {
+            tick: 0,
+            tick_interval_ms: 700,
+            money: START_MONEY,
+            effects: vec![],
+            spawn_rates: SpawnRates::uniform(SpawnCurve::Off),
+            // lane: Lane(Default::default()), //needs #[derive(Default)] on Field
+            // lane: Lane(std::array::from_fn(|_| Field(VecDeque::new()))), //creates an array by repeatedly calling the closure
+
+            // grid: Grid(Default::default()),
+            // grid: Grid(std::array::from_fn(|_| { Some(Lane(Default::default())) } )),
+            grid: Grid([None, None, Some(Rc::new(Lane(Default::default()))), None, None]),
+        };
+
+        state.set_spawn_curve(
+            2,
+            SpawnCurve::LinearRamp {
+                from: 4,
+                to: 1,
+                over_ticks: 60,
+            },
+        );
+
+        return state;
+    }
+
+    pub fn next(&mut self) {
+        self.tick += 1;
+
+        self.effects.retain_mut(|effect| {
+            effect.ticks_left = effect.ticks_left.saturating_sub(1);
+            effect.ticks_left > 0
+        });
+        State::grid_next(&mut self.grid, self.tick, &mut self.spawn_rates, &mut self.effects);
+    }
+
+    /// the real time between two ticks
+    pub fn tick_interval_ms(&self) -> u16 {
+        return self.tick_interval_ms;
+    }
+
+    pub fn effects_at(&self, lane: usize, field: usize) -> impl Iterator<Item = &Effect> {
+        self.effects
+            .iter()
+            .filter(move |effect| effect.lane == lane && effect.field == field)
+    }
+
+    /// effects the last tick produced, they have not lost any of their lifetime yet
+    pub fn new_effects(&self) -> impl Iterator<Item = &Effect> {
+        self.effects
+            .iter()
+            .filter(|effect| effect.ticks_left == effect.kind.lifetime())
+    }
+
+    pub fn set_spawn_curve(&mut self, lane: usize, curve: SpawnCurve) {
+        self.spawn_rates.zombies[lane] = curve;
+    }
+
+    pub fn turret_at(&self, lane: usize, field: usize) -> Option<&Turret> {
+        let lane = self.grid.get(lane)?.as_ref()?;
+        lane.get(field)?.iter().find_map(|entity| match entity {
+            Entity::Turret(turret) => Some(turret),
+            _ => None,
+        })
+    }
+
+    fn turret_at_mut(
+        &mut self,
+        player: PlayerId,
+        lane: usize,
+        field: usize,
+    ) -> Result<&mut Turret, ActionError> {
+        match self.grid.get(lane) {
+            Some(Some(_)) => {}
+            _ => return Err(ActionError::NoLane),
+        }
+        /* a rejected action must not copy a shared lane */
+        match self.turret_at(lane, field) {
+            Some(turret) if turret.owner != player => return Err(ActionError::NotOwner),
+            Some(_) => {}
+            None => return Err(ActionError::NoTurret),
+        }
+
+        let turret = self
+            .grid
+            .lane_mut(lane)
+            .unwrap()
+            .get_mut(field)
+            .ok_or(ActionError::NoTurret)?
+            .iter_mut()
+            .find_map(|entity| match entity {
+                Entity::Turret(turret) => Some(turret),
+                _ => None,
+            })
+            .ok_or(ActionError::NoTurret)?;
+
+        return Ok(turret);
+    }
+
+    /// a new turret of `player` for the placement price of its first tier
+    pub fn place_turret(
+        &mut self,
+        player: PlayerId,
+        lane: usize,
+        field: usize,
+        kind: TurretKind,
+    ) -> Result<(), ActionError> {
+        let cost = TURRET_TIERS[0].cost;
+        if cost > self.money {
+            return Err(ActionError::InsufficientFunds);
+        }
+
+        self.place_entity(lane, field, Entity::Turret(Turret::new(kind, player)))?;
+        self.money -= cost;
+
+        return Ok(());
+    }
+
+    pub fn upgrade_turret(
+        &mut self,
+        player: PlayerId,
+        lane: usize,
+        field: usize,
+    ) -> Result<(), ActionError> {
+        let money = self.money;
+        let turret = self.turret_at_mut(player, lane, field)?;
+        let cost = turret.upgrade_cost().ok_or(ActionError::MaxTier)?;
+
+        if cost > money {
+            return Err(ActionError::InsufficientFunds);
+        }
+
+        turret.tier += 1;
+        self.money -= cost;
+
+        return Ok(());
+    }
+
+    /// removes the turret from its field and returns the refunded money
+    pub fn sell_turret(
+        &mut self,
+        player: PlayerId,
+        lane: usize,
+        field: usize,
+    ) -> Result<Money, ActionError> {
+        let refund = self.turret_at_mut(player, lane, field)?.refund();
+
+        // turret_at_mut already made sure the lane and field exist
+        let lane = self.grid.lane_mut(lane).unwrap();
+        lane[field].retain(|entity| !matches!(entity, Entity::Turret(_)));
+        self.money += refund;
+
+        return Ok(refund);
+    }
+
+    /// puts `entity` on top of a field, as long as the field keeps following the rules documented on `Field`
+    pub fn place_entity(&mut self, lane: usize, field: usize, entity: Entity) -> Result<(), ActionError> {
+        let mut placed = match self.grid.get(lane) {
+            Some(Some(lane)) => lane.get(field).ok_or(ActionError::NoField)?.clone(),
+            _ => return Err(ActionError::NoLane),
+        };
+        placed.push_back(entity);
+        if !placed.is_consistent() {
+            return Err(ActionError::Occupied);
+        }
+
+        self.grid.lane_mut(lane).unwrap()[field] = placed;
+        return Ok(());
+    }
+
+    /// advances every lane in place, no field gets copied
+    fn grid_next(grid: &mut Grid, tick: u32, spawn_rates: &mut SpawnRates, effects: &mut Vec<Effect>) {
+        /* reused by every entity, so effects cost no allocation once it has grown */
+        let mut new_effects: Vec<EffectKind> = vec![];
+
+        for i in 0..grid.len() {
+            match &mut grid.0[i] {
+                /* nothing on an empty lane can change, keep sharing it */
+                Some(lane) if lane.iter().any(|field| !field.is_empty()) => {
+                    State::lane_next(Rc::make_mut(lane), i, tick, effects, &mut new_effects)
+                }
+                _ => {}
+            }
+        }
+
+        //handle zombie spawn
+        for i in 0..grid.len() {
+            if grid[i].is_none() || !spawn_rates.zombie_due(i, tick) {
+                continue;
+            }
+            spawn_rates.last_zombie_spawns[i] = tick;
+
+            let lane = grid.lane_mut(i).unwrap();
+            let i_last = lane.len() - 1;
+            Zombie::new(tick, ZOMBIE_HEALTH).enter(&mut lane[i_last], tick, &mut new_effects);
+            effects.extend(new_effects.drain(..).map(|kind| Effect::new(kind, i, i_last)));
+        }
+    }
+
+    fn lane_next(
+        lane: &mut Lane,
+        i_lane: usize,
+        tick: u32,
+        effects: &mut Vec<Effect>,
+        new_effects: &mut Vec<EffectKind>,
+    ) {
+        for i in 0..lane.len() {
+            /* Each entity of the stack is popped from the front and, if it stays, pushed
+             * back, so the stack ends up in its old order without a second buffer.
+             * Entities that moved in from field i - 1 this tick are part of the stack. */
+            for _ in 0..lane[i].len() {
+                let entity = lane[i].pop_front().unwrap();
+
+                let mut ctx = TickContext::new(tick, lane, i, new_effects);
+                let stays = entity.tick(&mut ctx);
+                let i_affected = ctx.i_affected();
+                effects.extend(new_effects.drain(..).map(|kind| Effect::new(kind, i_lane, i_affected)));
+
+                if let Some(entity) = stays {
+                    lane[i].push_back(entity);
+                }
+            }
+        }
+
+        debug_assert!(lane.iter().all(Field::is_consistent));
+    }
+
+}
+
+impl Grid {
+    /// the lane to change, a lane still shared with another state gets copied first
+    pub fn lane_mut(&mut self, i: usize) -> Option<&mut Lane> {
+        return self.0.get_mut(i)?.as_mut().map(Rc::make_mut);
+    }
+
+    pub fn set_lane(&mut self, i: usize, lane: Option<Lane>) {
+        self.0[i] = lane.map(Rc::new);
+    }
+
+    /// (lane, field) of every field that differs between the grids,
+    /// a lane that exists in only one of them differs in all of its fields
+    pub fn changed_fields(&self, other: &Grid) -> Vec<(usize, usize)> {
+        let mut changed = vec![];
+        for (i_lane, (a, b)) in self.iter().zip(other.iter()).enumerate() {
+            let fields = match (a, b) {
+                (Some(a), Some(b)) => a.len().max(b.len()),
+                (Some(lane), None) | (None, Some(lane)) => lane.len(),
+                (None, None) => 0,
+            };
+            for i_field in 0..fields {
+                if a.as_ref().map(|lane| &lane[i_field]) != b.as_ref().map(|lane| &lane[i_field]) {
+                    changed.push((i_lane, i_field));
+                }
+            }
+        }
+        return changed;
+    }
+}
+
+impl Effect {
+    pub fn new(kind: EffectKind, lane: usize, field: usize) -> Effect {
+        Effect {
+            kind,
+            lane,
+            field,
+            ticks_left: kind.lifetime(),
+        }
+    }
+}
+
+impl EffectKind {
+    /// in ticks
+    pub fn lifetime(&self) -> Tick {
+        match self {
+            EffectKind::Explosion => 3,
+            EffectKind::DeathPuff => 2,
+            EffectKind::MuzzleFlash => 1,
+            EffectKind::HitSpark => 1,
+        }
+    }
+
+    /// drawn on top of the field the effect belongs to
+    pub fn glyph(&self) -> &'static str {
+        match self {
+            EffectKind::Explosion => "*",
+            EffectKind::DeathPuff => "x",
+            EffectKind::MuzzleFlash => "'",
+            EffectKind::HitSpark => "+",
+        }
+    }
+}
+
+impl Field {
+    /// only counts targets, zombies that are still in the game
+    pub fn has_zombie(&self) -> bool {
+        self.iter().any(|entity| entity.behavior().is_target())
+    }
+
+    pub fn has_turret(&self) -> bool {
+        self.iter().any(|entity| entity.behavior().role() == Role::Defender)
+    }
+
+    /// whether an entity on the field is an obstacle to a zombie walking in
+    pub(crate) fn blocks_zombies(&self, current_tick: Tick) -> bool {
+        self.iter().any(|entity| entity.behavior().is_obstacle(current_tick))
+    }
+
+    /// applies `damage` to the first target of the field
+    pub(crate) fn hit_target(&mut self, damage: Damage, current_tick: Tick, effects: &mut Vec<EffectKind>) {
+        let target = self.iter_mut().find(|entity| entity.behavior().is_target());
+
+        if let Some(entity) = target {
+            entity.behavior_mut().take_hit(damage, current_tick, effects);
+        }
+    }
+
+    /// whether the stack follows the rules documented on `Field`
+    pub fn is_consistent(&self) -> bool {
+        let count = |role: Role| {
+            self.iter()
+                .map(Entity::behavior)
+                .filter(|behavior| behavior.role() == role && (role != Role::Attacker || behavior.is_target()))
+                .count()
+        };
+        let zombies = count(Role::Attacker);
+        let turrets = count(Role::Defender);
+        let bullets = count(Role::Projectile);
+
+        turrets <= 1 && (zombies == 0 || turrets + bullets == 0)
+    }
+}
+
+impl SpawnRates {
+    fn uniform(curve: SpawnCurve) -> SpawnRates {
+        SpawnRates {
+            zombies: std::array::from_fn(|_| curve.clone()),
+            last_zombie_spawns: [0; 5],
+        }
+    }
+
+    fn zombie_due(&self, lane: usize, tick: Tick) -> bool {
+        let interval = self.zombies[lane].interval_at(tick);
+        /* a loaded level may have spawned later than the tick it starts at */
+        interval != 0 && tick.saturating_sub(self.last_zombie_spawns[lane]) >= interval
+    }
+}
+
+impl SpawnCurve {
+    pub fn interval_at(&self, tick: Tick) -> Tick {
+        match *self {
+            SpawnCurve::Off => 0,
+            SpawnCurve::Constant(interval) => interval,
+            SpawnCurve::LinearRamp { from, to, over_ticks } => {
+                if tick >= over_ticks {
+                    return to;
+                }
+
+                let (from, to) = (from as i64, to as i64);
+                let progressed = from + (to - from) * tick as i64 / over_ticks as i64;
+                return progressed as Tick;
+            }
+            SpawnCurve::Step { before, after, at } => {
+                if tick < at {
+                    before
+                } else {
+                    after
+                }
+            }
+            SpawnCurve::Sine { min, max, period } => {
+                if period == 0 {
+                    return min;
+                }
+
+                /* 64 steps per full wave, 16 per quarter */
+                let step = (tick % period) as u64 * 64 / period as u64;
+                let quarter = (step % 16) as usize;
+                let sine = match step / 16 {
+                    0 => QUARTER_SINE_PERMILLE[quarter],
+                    1 => QUARTER_SINE_PERMILLE[16 - quarter],
+                    2 => -QUARTER_SINE_PERMILLE[quarter],
+                    _ => -QUARTER_SINE_PERMILLE[16 - quarter],
+                };
+
+                let (min, max) = (min as i64, max as i64);
+                let middle = (min + max) / 2;
+                let amplitude = (max - min) / 2;
+                return (middle + amplitude * sine / 1000) as Tick;
+            }
+        }
+    }
+}
+
+// impl Grid {
+//     pub fn len(&self) -> usize {
+//         self.0.len()
+//     }
+// }
+impl core::ops::Deref for Grid {
+    type Target = [Option<Rc<Lane>>; 5];
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+// // DerefMut is optional
+impl core::ops::DerefMut for Grid {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl core::ops::Deref for Lane {
+    type Target = [Field; 9];
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl core::ops::DerefMut for Lane {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl core::ops::Deref for Field {
+    type Target = VecDeque<Entity>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl core::ops::DerefMut for Field {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+// ############################################################################
+// ############################################################################
+// ############################################################################
+// ############################################################################
+// ############################################################################
+// ############################################################################
+// ############################################################################
+// ############################################################################
+// ############################################################################
+// ############################################################################
+// ############################################################################
+// ############################################################################
+// ############################################################################
+// ############################################################################
+// ############################################################################
+// ############################################################################
+// ############################################################################
+// ############################################################################
+// ############################################################################
+// ############################################################################
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ParseBoardError, ATTACK_TICKS, SELL_REFUND_PERCENT};
+
+    fn ready_turret(kind: TurretKind) -> Turret {
+        Turret { cooldown: 0, ..Turret::new(kind, LOCAL_PLAYER) }
+    }
+
+    /// the default level with `lane` as its only lane and no spawns
+    fn lane_state(lane: &str, money: Money) -> State {
+        let mut state = State::new();
+        state.set_spawn_curve(2, SpawnCurve::Off);
+        state.grid.set_lane(2, Some(lane.parse().unwrap()));
+        state.money = money;
+        return state;
+    }
+
+    #[test]
+    fn snapshots_share_lanes_a_tick_did_not_change() {
+        let mut state = State::new();
+        state.grid.set_lane(0, Some(Lane::default()));
+        state.grid.lane_mut(2).unwrap()[5].push_back(Entity::Zombie(Zombie::new(0, ZOMBIE_HEALTH)));
+        let snapshot = state.clone();
+
+        state.next();
+
+        let shared = |i: usize| Rc::ptr_eq(snapshot.grid[i].as_ref().unwrap(), state.grid[i].as_ref().unwrap());
+        /* the empty lane is still the same, the lane with the zombie got copied */
+        assert!(shared(0));
+        assert!(!shared(2));
+        assert_eq!(&1, &snapshot.grid[2].as_ref().unwrap()[5].len());
+        assert_eq!(&true, &snapshot.grid[2].as_ref().unwrap()[4].is_empty());
+    }
+
+    #[test]
+    fn changed_fields_between_ticks() {
+        let mut state = State::new();
+        state.grid.lane_mut(2).unwrap()[5].push_back(Entity::Zombie(Zombie::new(0, ZOMBIE_HEALTH)));
+        let before = state.grid.clone();
+
+        state.next();
+
+        assert_eq!(vec![(2, 4), (2, 5)], before.changed_fields(&state.grid));
+        assert_eq!(Vec::<(usize, usize)>::new(), state.grid.changed_fields(&state.grid));
+
+        let mut without_lane = state.grid.clone();
+        without_lane[2] = None;
+        assert_eq!(9, state.grid.changed_fields(&without_lane).len());
+    }
+
+    #[test]
+    fn zombie_spawns_follow_linear_ramp() {
+        let mut state = State {
+            tick: 0,
+            tick_interval_ms: 700,
+            money: 0,
+            effects: vec![],
+            spawn_rates: SpawnRates::uniform(SpawnCurve::LinearRamp {
+                from: 4,
+                to: 2,
+                over_ticks: 8,
+            }),
+            grid: Grid([None, None, Some(Rc::new(Lane(Default::default()))), None, None]),
+        };
+
+        let mut spawn_ticks = vec![];
+        for _ in 0..12 {
+            state.next();
+            let spawned = state.grid[2].as_ref().unwrap().0[8]
+                .iter()
+                .any(|entity| entity == &Entity::Zombie(Zombie::new(state.tick, ZOMBIE_HEALTH)));
+            if spawned {
+                spawn_ticks.push(state.tick);
+            }
+        }
+
+        assert_eq!(vec![4, 7, 9, 11], spawn_ticks);
+    }
+
+    #[test]
+    fn spawn_after_the_current_tick_waits_instead_of_underflowing() {
+        let mut state = State::new();
+        state.set_spawn_curve(2, SpawnCurve::Constant(1));
+        state.spawn_rates.last_zombie_spawns[2] = 10;
+
+        state.next();
+        assert!(state.grid[2].as_ref().unwrap()[8].is_empty());
+    }
+
+    #[test]
+    fn spawn_curve_intervals() {
+        let step = SpawnCurve::Step { before: 5, after: 2, at: 10 };
+        assert_eq!(5, step.interval_at(9));
+        assert_eq!(2, step.interval_at(10));
+
+        let ramp = SpawnCurve::LinearRamp { from: 10, to: 2, over_ticks: 4 };
+        assert_eq!(10, ramp.interval_at(0));
+        assert_eq!(6, ramp.interval_at(2));
+        assert_eq!(2, ramp.interval_at(4));
+        assert_eq!(2, ramp.interval_at(100));
+
+        let sine = SpawnCurve::Sine { min: 2, max: 10, period: 64 };
+        assert_eq!(6, sine.interval_at(0));
+        assert_eq!(10, sine.interval_at(16));
+        assert_eq!(6, sine.interval_at(32));
+        assert_eq!(2, sine.interval_at(48));
+        assert_eq!(6, sine.interval_at(64));
+
+        assert_eq!(0, SpawnCurve::Off.interval_at(3));
+    }
+
+    #[test]
+    fn effect_without_ticks_left_is_dropped() {
+        let mut state = State::new();
+        state.effects.push(Effect { ticks_left: 0, ..Effect::new(EffectKind::HitSpark, 2, 0) });
+
+        state.next();
+        assert!(state.effects.is_empty());
+    }
+
+    #[test]
+    fn effects_expire_after_their_lifetime() {
+        let mut state = lane_state("T(cd=10) Z _ _ _ _ _ _ _", 0);
+
+        /* the zombie attacks the turret before trampling it */
+        for _ in 0..=ATTACK_TICKS {
+            state.next();
+        }
+        assert_eq!(
+            vec![Effect::new(EffectKind::Explosion, 2, 0)],
+            state.effects
+        );
+
+        for _ in 1..EffectKind::Explosion.lifetime() {
+            state.next();
+            assert_eq!(1, state.effects.len());
+        }
+
+        state.next();
+        assert_eq!(&true, &state.effects.is_empty());
+    }
+
+    #[test]
+    fn turret_shot_shows_muzzle_flash() {
+        let mut state = lane_state("T(rapid,cd=0) _ _ _ Z(hp=9) _ _ _ _", 0);
+
+        state.next();
+        assert_eq!(
+            vec![Effect::new(EffectKind::MuzzleFlash, 2, 1)],
+            state.effects
+        );
+    }
+
+    #[test]
+    fn field_stacking_rules() {
+        /* parsing checks a stack with `Field::is_consistent` */
+        assert!("[ZZ]".parse::<Field>().is_ok());
+        assert!("[T°]".parse::<Field>().is_ok());
+        for stack in ["[TT]", "[TZ]", "[°Z]"] {
+            assert!(matches!(stack.parse::<Field>(), Err(ParseBoardError::Inconsistent(_))), "{}", stack);
+        }
+    }
+
+    #[test]
+    fn upgraded_turret_shoots_stronger_bullets() {
+        let mut state = lane_state("T(rapid,cd=0) _ _ _ _ Z(hp=9) _ _ _", 100);
+
+        assert_eq!(Ok(()), state.upgrade_turret(LOCAL_PLAYER, 2, 0));
+        assert_eq!(100 - TURRET_TIERS[1].cost, state.money);
+        assert_eq!(Some(&Turret { tier: 1, ..ready_turret(TurretKind::Rapid) }), state.turret_at(2, 0));
+
+        state.next();
+        assert_eq!(format!("°(t=1,dmg={})", TURRET_TIERS[1].damage), state.grid[2].as_ref().unwrap()[1].to_string());
+    }
+
+    #[test]
+    fn turret_upgrade_is_validated() {
+        let mut state = lane_state("T T(owner=1) T(tier=2) _ _ _ _ _ _", TURRET_TIERS[1].cost - 1);
+
+        assert_eq!(Err(ActionError::InsufficientFunds), state.upgrade_turret(LOCAL_PLAYER, 2, 0));
+        assert_eq!(Err(ActionError::NotOwner), state.upgrade_turret(LOCAL_PLAYER, 2, 1));
+        assert_eq!(Err(ActionError::MaxTier), state.upgrade_turret(LOCAL_PLAYER, 2, 2));
+        assert_eq!(Err(ActionError::NoTurret), state.upgrade_turret(LOCAL_PLAYER, 2, 3));
+        assert_eq!(Err(ActionError::NoLane), state.upgrade_turret(LOCAL_PLAYER, 0, 0));
+        assert_eq!(TURRET_TIERS[1].cost - 1, state.money);
+        assert_eq!(Some(&Turret::new(TurretKind::Basic, LOCAL_PLAYER)), state.turret_at(2, 0));
+    }
+
+    #[test]
+    fn sell_turret_refunds_money() {
+        let mut state = lane_state("T(tier=1) T(owner=1) _ _ _ _ _ _ _", 0);
+
+        let refund = (TURRET_TIERS[0].cost + TURRET_TIERS[1].cost) * SELL_REFUND_PERCENT / 100;
+
+        assert_eq!(Ok(refund), state.sell_turret(LOCAL_PLAYER, 2, 0));
+        assert_eq!(refund, state.money);
+        assert_eq!(&true, &state.grid[2].as_ref().unwrap().0[0].is_empty());
+
+        assert_eq!(Err(ActionError::NotOwner), state.sell_turret(LOCAL_PLAYER, 2, 1));
+        assert_eq!(Err(ActionError::NoTurret), state.sell_turret(LOCAL_PLAYER, 2, 0));
+        assert_eq!(refund, state.money);
+    }
+
+    #[test]
+    fn place_entity_keeps_fields_consistent() {
+        let mut state = State::new();
+        let zombie = Entity::Zombie(Zombie::new(0, ZOMBIE_HEALTH));
+
+        assert_eq!(Ok(()), state.place_entity(2, 8, zombie.clone()));
+        assert_eq!(Ok(()), state.place_entity(2, 8, zombie.clone()));
+        assert_eq!(2, state.grid[2].as_ref().unwrap()[8].len());
+
+        let turret = Entity::Turret(Turret::new(TurretKind::Basic, LOCAL_PLAYER));
+        assert_eq!(Err(ActionError::Occupied), state.place_entity(2, 8, turret.clone()));
+        assert_eq!(Err(ActionError::NoField), state.place_entity(2, 9, turret.clone()));
+        assert_eq!(Err(ActionError::NoLane), state.place_entity(0, 0, turret));
+        assert_eq!(2, state.grid[2].as_ref().unwrap()[8].len());
+    }
+
+    #[test]
+    fn place_turret_charges_the_first_tier() {
+        let mut state = State::new();
+        state.money = TURRET_TIERS[0].cost;
+
+        assert_eq!(Ok(()), state.place_turret(LOCAL_PLAYER, 2, 3, TurretKind::Rapid));
+        assert_eq!(0, state.money);
+        assert_eq!(Some(&Turret::new(TurretKind::Rapid, LOCAL_PLAYER)), state.turret_at(2, 3));
+
+        assert_eq!(Err(ActionError::InsufficientFunds), state.place_turret(LOCAL_PLAYER, 2, 4, TurretKind::Basic));
+        state.money = 100;
+        assert_eq!(Err(ActionError::Occupied), state.place_turret(LOCAL_PLAYER, 2, 3, TurretKind::Basic));
+        assert_eq!(100, state.money);
+    }
+}
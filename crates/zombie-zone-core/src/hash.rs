@@ -0,0 +1,218 @@
+use crate::{Effect, EffectKind, Entity, Field, Lane, SpawnCurve, SpawnRates, State};
+
+pub type StateHash = u64;
+
+/// FNV-1a over a fixed little endian encoding of the state. Unlike
+/// `std::hash` it is fully specified, so every platform and every build
+/// computes the same hash for the same state.
+pub struct StableHasher(u64);
+
+impl StableHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    pub fn new() -> StableHasher {
+        return StableHasher(StableHasher::OFFSET_BASIS);
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(StableHasher::PRIME);
+        }
+    }
+
+    pub fn write_u8(&mut self, n: u8) {
+        self.write(&[n]);
+    }
+
+    pub fn write_u32(&mut self, n: u32) {
+        self.write(&n.to_le_bytes());
+    }
+
+    /// usize is hashed as u64 so 32 and 64 bit platforms agree
+    pub fn write_usize(&mut self, n: usize) {
+        self.write(&(n as u64).to_le_bytes());
+    }
+
+    pub fn finish(&self) -> StateHash {
+        return self.0;
+    }
+}
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        return StableHasher::new();
+    }
+}
+
+/// Feeds a value into a `StableHasher`. Enums write their variant as a tag
+/// byte first and sequences their length, so different states can not
+/// encode to the same bytes.
+pub trait StableHash {
+    fn stable_hash(&self, hasher: &mut StableHasher);
+}
+
+impl State {
+    /// the same on every platform and build, see `StableHasher`
+    pub fn hash(&self) -> StateHash {
+        let mut hasher = StableHasher::new();
+        self.stable_hash(&mut hasher);
+        return hasher.finish();
+    }
+}
+
+impl StableHash for State {
+    fn stable_hash(&self, hasher: &mut StableHasher) {
+        hasher.write_u32(self.tick);
+        hasher.write(&self.tick_interval_ms.to_le_bytes());
+        hasher.write_u32(self.money);
+        for lane in self.grid.iter() {
+            match lane {
+                Some(lane) => {
+                    hasher.write_u8(1);
+                    lane.stable_hash(hasher);
+                }
+                None => hasher.write_u8(0),
+            }
+        }
+        hasher.write_usize(self.effects.len());
+        for effect in &self.effects {
+            effect.stable_hash(hasher);
+        }
+        self.spawn_rates.stable_hash(hasher);
+    }
+}
+
+impl StableHash for Lane {
+    fn stable_hash(&self, hasher: &mut StableHasher) {
+        for field in self.iter() {
+            field.stable_hash(hasher);
+        }
+    }
+}
+
+impl StableHash for Field {
+    fn stable_hash(&self, hasher: &mut StableHasher) {
+        hasher.write_usize(self.len());
+        for entity in self.iter() {
+            entity.stable_hash(hasher);
+        }
+    }
+}
+
+impl StableHash for Entity {
+    fn stable_hash(&self, hasher: &mut StableHasher) {
+        hasher.write_u8(self.tag());
+        self.behavior().stable_hash(hasher);
+    }
+}
+
+impl StableHash for Effect {
+    fn stable_hash(&self, hasher: &mut StableHasher) {
+        hasher.write_u8(match self.kind {
+            EffectKind::Explosion => 0,
+            EffectKind::DeathPuff => 1,
+            EffectKind::MuzzleFlash => 2,
+            EffectKind::HitSpark => 3,
+        });
+        hasher.write_usize(self.lane);
+        hasher.write_usize(self.field);
+        hasher.write_u32(self.ticks_left);
+    }
+}
+
+impl StableHash for SpawnRates {
+    fn stable_hash(&self, hasher: &mut StableHasher) {
+        for curve in &self.zombies {
+            curve.stable_hash(hasher);
+        }
+        for tick in &self.last_zombie_spawns {
+            hasher.write_u32(*tick);
+        }
+    }
+}
+
+impl StableHash for SpawnCurve {
+    fn stable_hash(&self, hasher: &mut StableHasher) {
+        match *self {
+            SpawnCurve::Off => hasher.write_u8(0),
+            SpawnCurve::Constant(interval) => {
+                hasher.write_u8(1);
+                hasher.write_u32(interval);
+            }
+            SpawnCurve::LinearRamp { from, to, over_ticks } => {
+                hasher.write_u8(2);
+                hasher.write_u32(from);
+                hasher.write_u32(to);
+                hasher.write_u32(over_ticks);
+            }
+            SpawnCurve::Step { before, after, at } => {
+                hasher.write_u8(3);
+                hasher.write_u32(before);
+                hasher.write_u32(after);
+                hasher.write_u32(at);
+            }
+            SpawnCurve::Sine { min, max, period } => {
+                hasher.write_u8(4);
+                hasher.write_u32(min);
+                hasher.write_u32(max);
+                hasher.write_u32(period);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn hasher_is_fnv1a() {
+        let mut hasher = StableHasher::new();
+        hasher.write(b"a");
+        assert_eq!(0xaf63_dc4c_8601_ec8c, hasher.finish());
+    }
+
+    #[test]
+    fn equal_states_hash_equal() {
+        let mut a = State::new();
+        let mut b = State::new();
+        for _ in 0..20 {
+            a.next();
+            b.next();
+            assert_eq!(a.hash(), b.hash());
+        }
+    }
+
+    #[test]
+    fn hash_covers_the_whole_state() {
+        let state = State::new();
+
+        let mut ticked = state.clone();
+        ticked.tick += 1;
+        let mut richer = state.clone();
+        richer.money += 1;
+        let mut respawned = state.clone();
+        respawned.set_spawn_curve(0, SpawnCurve::Constant(3));
+        let mut slower = state.clone();
+        slower.tick_interval_ms += 1;
+        let mut sparkling = state.clone();
+        sparkling.effects.push(Effect::new(EffectKind::HitSpark, 2, 3));
+
+        for changed in [ticked, richer, respawned, slower, sparkling] {
+            assert_ne!(state.hash(), changed.hash());
+        }
+    }
+
+    #[test]
+    fn hash_is_stable_across_builds() {
+        /* pinned, a change here breaks every stored replay hash */
+        let mut state = State::default();
+        for _ in 0..10 {
+            state.next();
+        }
+        assert_eq!(0x26d1_2f1e_dfe2_864f, state.hash());
+    }
+}
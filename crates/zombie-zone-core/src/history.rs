@@ -0,0 +1,137 @@
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+/// A bounded stack of earlier values, once full the oldest value is dropped.
+/// Values are kept behind `Rc`, so pushing a value that is still in use
+/// elsewhere, or the same value twice, shares it instead of copying it.
+#[derive(Clone)]
+pub struct History<T> {
+    capacity: usize,
+    /// oldest first
+    entries: VecDeque<Rc<T>>,
+}
+
+impl<T> History<T> {
+    pub fn new(capacity: usize) -> History<T> {
+        return History {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        };
+    }
+
+    pub fn capacity(&self) -> usize {
+        return self.capacity;
+    }
+
+    /// shrinking drops the oldest values that no longer fit
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        return self.entries.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.entries.is_empty();
+    }
+
+    pub fn push(&mut self, value: Rc<T>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(value);
+    }
+
+    /// the most recent value
+    pub fn pop(&mut self) -> Option<Rc<T>> {
+        return self.entries.pop_back();
+    }
+
+    /// Pops values as long as `stop` does not accept the popped one and
+    /// returns the last popped value, which is the oldest value if none got
+    /// accepted.
+    pub fn pop_until(&mut self, stop: impl Fn(&T) -> bool) -> Option<Rc<T>> {
+        let mut popped = None;
+        while let Some(value) = self.entries.pop_back() {
+            let accepted = stop(&value);
+            popped = Some(value);
+            if accepted {
+                break;
+            }
+        }
+        return popped;
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Two histories are equal when they hold the very same values, comparing
+/// the values themselves would be far too slow for a history of states.
+impl<T> PartialEq for History<T> {
+    fn eq(&self, other: &Self) -> bool {
+        return self.capacity == other.capacity
+            && self.entries.len() == other.entries.len()
+            && self.entries.iter().zip(&other.entries).all(|(a, b)| Rc::ptr_eq(a, b));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn history_drops_the_oldest_state_when_full() {
+        let mut history = History::new(3);
+        let mut state = State::new();
+        for _ in 0..5 {
+            history.push(Rc::new(state.clone()));
+            state.next();
+        }
+
+        assert_eq!(3, history.len());
+        assert_eq!(4, history.pop().unwrap().tick);
+        assert_eq!(3, history.pop().unwrap().tick);
+        assert_eq!(2, history.pop().unwrap().tick);
+        assert!(history.pop().is_none());
+    }
+
+    #[test]
+    fn history_steps_back_several_ticks() {
+        let mut history = History::new(10);
+        let mut state = State::new();
+        for _ in 0..5 {
+            history.push(Rc::new(state.clone()));
+            state.next();
+        }
+
+        assert_eq!(2, history.pop_until(|earlier| earlier.tick <= 2).unwrap().tick);
+        assert_eq!(2, history.len());
+        /* more ticks than remembered end at the oldest state */
+        assert_eq!(0, history.pop_until(|_| false).unwrap().tick);
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    fn shrinking_history_keeps_the_latest_states() {
+        let mut history = History::new(5);
+        let mut state = State::new();
+        for _ in 0..5 {
+            history.push(Rc::new(state.clone()));
+            state.next();
+        }
+
+        history.set_capacity(2);
+        assert_eq!(2, history.len());
+        assert_eq!(4, history.pop().unwrap().tick);
+    }
+}
@@ -0,0 +1,28 @@
+//! the zombie zone simulation, free of any ui code
+#![allow(clippy::needless_return, clippy::single_match)]
+
+mod entity;
+mod game;
+mod hash;
+mod history;
+mod replay;
+mod scenario;
+mod scheduler;
+mod text;
+#[cfg(feature = "serde")]
+mod save;
+#[cfg(feature = "scripting")]
+mod script;
+
+pub use entity::*;
+pub use game::*;
+pub use hash::*;
+pub use history::*;
+pub use replay::*;
+pub use scenario::*;
+pub use scheduler::*;
+pub use text::*;
+#[cfg(feature = "serde")]
+pub use save::*;
+#[cfg(feature = "scripting")]
+pub use script::*;
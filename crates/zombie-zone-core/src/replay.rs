@@ -0,0 +1,248 @@
+use crate::{ActionError, Money, PlayerId, State, StateHash, Tick, TurretKind};
+
+/// Something a player does to the game. Everything else that happens is
+/// decided by `State::next`, so a start state and the actions are a full game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Action {
+    PlaceTurret { player: PlayerId, lane: usize, field: usize, kind: TurretKind },
+    UpgradeTurret { player: PlayerId, lane: usize, field: usize },
+    SellTurret { player: PlayerId, lane: usize, field: usize },
+}
+
+impl State {
+    /// refund of a sold turret, 0 for everything else
+    pub fn apply(&mut self, action: &Action) -> Result<Money, ActionError> {
+        match *action {
+            Action::PlaceTurret { player, lane, field, kind } => {
+                return self.place_turret(player, lane, field, kind).map(|_| 0);
+            }
+            Action::UpgradeTurret { player, lane, field } => {
+                return self.upgrade_turret(player, lane, field).map(|_| 0);
+            }
+            Action::SellTurret { player, lane, field } => {
+                return self.sell_turret(player, lane, field);
+            }
+        }
+    }
+}
+
+/// A recorded game: the start state and every action with the tick it was
+/// applied on. Actions of a tick are applied before that tick's `next`, in
+/// the order they were recorded. Rejected actions are recorded as well, they
+/// get rejected again on playback.
+#[derive(Clone, PartialEq)]
+pub struct Replay {
+    pub start: State,
+    /// how often `next` got called after `start`
+    pub ticks: Tick,
+    /// sorted by tick
+    pub actions: Vec<(Tick, Action)>,
+    /// `hashes[i]` is the hash of the state after `i + 1` ticks, empty for
+    /// replays recorded without hashes
+    pub hashes: Vec<StateHash>,
+}
+
+impl Replay {
+    pub fn new(start: State) -> Replay {
+        return Replay {
+            start,
+            ticks: 0,
+            actions: vec![],
+            hashes: vec![],
+        };
+    }
+
+    /// the tick the recording is at, matching the tick of the recorded state
+    pub fn tick(&self) -> Tick {
+        return self.start.tick + self.ticks;
+    }
+
+    pub fn record_action(&mut self, action: Action) {
+        self.actions.push((self.tick(), action));
+    }
+
+    /// records a `next` call, `state` is the state right after it
+    pub fn record_next(&mut self, state: &State) {
+        self.ticks += 1;
+        self.hashes.push(state.hash());
+    }
+
+    /// forgets everything after the first `ticks` ticks and `actions` actions
+    pub fn truncate(&mut self, ticks: Tick, actions: usize) {
+        self.ticks = self.ticks.min(ticks);
+        self.hashes.truncate(self.ticks as usize);
+        self.actions.truncate(actions);
+    }
+
+    /// Advances a state that is somewhere within the replay by one tick,
+    /// applying the actions recorded for the tick it is at.
+    pub fn step(&self, state: &mut State) {
+        let now = state.tick;
+        let first = self.actions.partition_point(|(tick, _)| *tick < now);
+        for (_, action) in self.actions[first..].iter().take_while(|(tick, _)| *tick == now) {
+            /* a rejection already happened the same way during recording */
+            let _ = state.apply(action);
+        }
+        state.next();
+    }
+
+    /// the state after `ticks` ticks of the replay
+    pub fn state_after(&self, ticks: Tick) -> State {
+        let mut state = self.start.clone();
+        for _ in 0..ticks.min(self.ticks) {
+            self.step(&mut state);
+        }
+        return state;
+    }
+
+    pub fn final_state(&self) -> State {
+        return self.state_after(self.ticks);
+    }
+
+    /// Plays the replay and compares every tick with the recorded hashes,
+    /// returns the first tick whose state differs from the recording.
+    pub fn first_divergence(&self) -> Option<Tick> {
+        let mut state = self.start.clone();
+        for hash in &self.hashes {
+            self.step(&mut state);
+            if state.hash() != *hash {
+                return Some(state.tick);
+            }
+        }
+        return None;
+    }
+}
+
+#[cfg(feature = "serde")]
+mod file {
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    use super::*;
+    use crate::{LoadError, SavedState};
+
+    /// The file shape of a replay. The start state is a `SavedState`, so an
+    /// old replay gets its start migrated like any other save, actions are
+    /// `[tick, action]` pairs to keep the file short.
+    #[derive(Serialize, Deserialize)]
+    struct ReplayFile {
+        start: Value,
+        ticks: Tick,
+        actions: Vec<(Tick, Action)>,
+        #[serde(default)]
+        hashes: Vec<StateHash>,
+    }
+
+    impl Replay {
+        pub fn to_json(&self) -> String {
+            let file = ReplayFile {
+                start: serde_json::to_value(SavedState::new(self.start.clone())).unwrap(),
+                ticks: self.ticks,
+                actions: self.actions.clone(),
+                hashes: self.hashes.clone(),
+            };
+            return serde_json::to_string(&file).unwrap();
+        }
+
+        pub fn from_json(json: &str) -> Result<Replay, LoadError> {
+            let file: ReplayFile = serde_json::from_str(json).map_err(LoadError::Malformed)?;
+
+            return Ok(Replay {
+                start: SavedState::from_value(file.start)?.state,
+                ticks: file.ticks,
+                actions: file.actions,
+                hashes: file.hashes,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    /// plays a game where the player upgrades and later sells the turret
+    fn record_game() -> (Replay, State) {
+        let mut state = State::default();
+        let mut replay = Replay::new(state.clone());
+
+        for tick in 0..12 {
+            let action = match tick {
+                2 => Some(Action::UpgradeTurret { player: LOCAL_PLAYER, lane: 2, field: 0 }),
+                /* rejected, there is no turret here */
+                5 => Some(Action::SellTurret { player: LOCAL_PLAYER, lane: 2, field: 4 }),
+                9 => Some(Action::SellTurret { player: LOCAL_PLAYER, lane: 2, field: 0 }),
+                _ => None,
+            };
+            match action {
+                Some(action) => {
+                    let _ = state.apply(&action);
+                    replay.record_action(action);
+                }
+                None => {}
+            }
+
+            state.next();
+            replay.record_next(&state);
+        }
+
+        return (replay, state);
+    }
+
+    #[test]
+    fn replay_reproduces_the_game() {
+        let (replay, state) = record_game();
+
+        assert_eq!(12, replay.tick());
+        assert_eq!(3, replay.actions.len());
+        assert!(state == replay.final_state());
+    }
+
+    #[test]
+    fn replay_steps_through_the_game() {
+        let (replay, _) = record_game();
+
+        let mut state = replay.start.clone();
+        for ticks in 1..=replay.ticks {
+            replay.step(&mut state);
+            assert!(state == replay.state_after(ticks));
+        }
+    }
+
+    #[test]
+    fn truncated_replay_ends_earlier() {
+        let (mut replay, _) = record_game();
+        let expected = replay.state_after(7);
+
+        /* by tick 7 the upgrade and the rejected sell are done, the real sell is not */
+        replay.truncate(7, 2);
+
+        assert_eq!(7, replay.ticks);
+        assert_eq!(7, replay.hashes.len());
+        assert_eq!(2, replay.actions.len());
+        assert!(expected == replay.final_state());
+    }
+
+    #[test]
+    fn replay_finds_the_tick_where_a_run_diverges() {
+        let (mut replay, _) = record_game();
+        assert_eq!(None, replay.first_divergence());
+
+        /* as if the recording game had a different rule from tick 7 on */
+        replay.hashes[6] ^= 1;
+        assert_eq!(Some(7), replay.first_divergence());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn replay_survives_its_file_format() {
+        let (replay, state) = record_game();
+
+        let loaded = Replay::from_json(&replay.to_json()).unwrap();
+
+        assert!(replay == loaded);
+        assert!(state == loaded.final_state());
+    }
+}
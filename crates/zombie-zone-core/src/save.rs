@@ -0,0 +1,210 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::State;
+
+/// Version of the serialized state shape, bump it whenever the shape of
+/// `State` or anything inside it changes.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// `MIGRATIONS[i]` turns a save of schema version `i + 1` into one of version
+/// `i + 2`, every bump of `SCHEMA_VERSION` appends its migration here.
+const MIGRATIONS: [fn(&mut Value); SCHEMA_VERSION as usize - 1] = [];
+
+/// The serialized form of a game, always write a `State` through this so a
+/// reader knows which shape it got. As JSON, schema version 1 looks like:
+///
+/// ```json
+/// {
+///   "schema_version": 1,
+///   "state": {
+///     "tick_interval_ms": 700,
+///     "tick": 3,
+///     "grid": [null, null, [[{"Turret": {"kind": "Basic", "tier": 0, "owner": 0, "cooldown": 1}}], [], ...], null, null],
+///     "money": 100,
+///     "effects": [{"kind": "MuzzleFlash", "lane": 2, "field": 1, "ticks_left": 1}],
+///     "spawn_rates": {
+///       "zombies": ["Off", "Off", {"LinearRamp": {"from": 4, "to": 1, "over_ticks": 60}}, "Off", "Off"],
+///       "last_zombie_spawns": [0, 0, 0, 0, 0]
+///     }
+///   }
+/// }
+/// ```
+///
+/// - the grid is 5 lanes, a missing lane is `null`, a lane is 9 fields and a
+///   field is the list of its entities in stack order
+/// - an entity is one of `{"Zombie": {"last_moved", "health", "behaviour"}}`,
+///   `{"Turret": {"kind", "tier", "owner", "cooldown"}}` or
+///   `{"Bullet": [last_moved, damage]}`
+/// - a behaviour is `"Walking"`, `"Blocked"` or one of `Attacking`, `Stunned`,
+///   `Dying` as `{"Stunned": {"until": tick}}`
+/// - a spawn curve is `"Off"`, `{"Constant": interval}` or one of `LinearRamp`,
+///   `Step`, `Sine` with the fields of `SpawnCurve`
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedState {
+    pub schema_version: u32,
+    pub state: State,
+}
+
+impl SavedState {
+    pub fn new(state: State) -> SavedState {
+        return SavedState {
+            schema_version: SCHEMA_VERSION,
+            state,
+        };
+    }
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    /// there is no schema version to tell the shape of the save
+    MissingVersion,
+    /// written by a newer game, or by none at all
+    UnknownVersion(u64),
+    /// the save does not have the shape of its schema version
+    Malformed(serde_json::Error),
+    /// an effect with no ticks left, it would have been gone before the save
+    ExpiredEffect,
+}
+
+impl SavedState {
+    /// reads a save of any known schema version, older saves are migrated first
+    pub fn from_value(value: Value) -> Result<SavedState, LoadError> {
+        return SavedState::migrate(value, &MIGRATIONS);
+    }
+
+    /// `from_value` with the migrations up to schema version `migrations.len() + 1`
+    fn migrate(mut value: Value, migrations: &[fn(&mut Value)]) -> Result<SavedState, LoadError> {
+        let current = migrations.len() as u64 + 1;
+        let version = match value.get("schema_version") {
+            Some(version) => version.as_u64().ok_or(LoadError::MissingVersion)?,
+            None => return Err(LoadError::MissingVersion),
+        };
+        if version == 0 || version > current {
+            return Err(LoadError::UnknownVersion(version));
+        }
+
+        for migrate in &migrations[version as usize - 1..] {
+            migrate(&mut value);
+        }
+        value["schema_version"] = Value::from(current);
+
+        let saved: SavedState = serde_json::from_value(value).map_err(LoadError::Malformed)?;
+        if saved.state.effects.iter().any(|effect| effect.ticks_left == 0) {
+            return Err(LoadError::ExpiredEffect);
+        }
+        return Ok(saved);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn state_survives_a_json_round_trip() {
+        let mut state = State::new();
+        for _ in 0..10 {
+            state.next();
+        }
+
+        let json = serde_json::to_string(&SavedState::new(state.clone())).unwrap();
+        let saved: SavedState = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(SCHEMA_VERSION, saved.schema_version);
+        assert!(state == saved.state);
+    }
+
+    #[test]
+    fn current_save_loads() {
+        let mut state = State::new();
+        state.next();
+
+        let value = serde_json::to_value(SavedState::new(state.clone())).unwrap();
+        let saved = SavedState::from_value(value).unwrap();
+
+        assert!(state == saved.state);
+    }
+
+    #[test]
+    fn older_saves_get_migrated() {
+        /* a made up schema version 1 that called the money gold */
+        fn rename_gold(value: &mut Value) {
+            let state = value["state"].as_object_mut().unwrap();
+            let gold = state.remove("gold").unwrap();
+            state.insert(String::from("money"), gold);
+        }
+        let migrations: [fn(&mut Value); 1] = [rename_gold];
+
+        let mut state = State::new();
+        state.money = 42;
+        let mut value = serde_json::to_value(SavedState::new(state.clone())).unwrap();
+        let money = value["state"].as_object_mut().unwrap().remove("money").unwrap();
+        value["state"]["gold"] = money;
+        value["schema_version"] = Value::from(1);
+
+        let saved = SavedState::migrate(value, &migrations).unwrap();
+        assert_eq!(2, saved.schema_version);
+        assert!(state == saved.state);
+
+        /* a save of the current version skips every migration */
+        let value = serde_json::to_value(SavedState { schema_version: 2, state: state.clone() }).unwrap();
+        assert!(state == SavedState::migrate(value, &migrations).unwrap().state);
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        let mut value = serde_json::to_value(SavedState::new(State::new())).unwrap();
+
+        value["schema_version"] = Value::from(SCHEMA_VERSION + 1);
+        assert!(matches!(
+            SavedState::from_value(value.clone()),
+            Err(LoadError::UnknownVersion(v)) if v == SCHEMA_VERSION as u64 + 1
+        ));
+
+        value.as_object_mut().unwrap().remove("schema_version");
+        assert!(matches!(
+            SavedState::from_value(value),
+            Err(LoadError::MissingVersion)
+        ));
+    }
+
+    #[test]
+    fn malformed_save_is_rejected() {
+        let value = serde_json::json!({"schema_version": SCHEMA_VERSION, "state": {"tick": 1}});
+
+        assert!(matches!(
+            SavedState::from_value(value),
+            Err(LoadError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn expired_effects_are_rejected() {
+        let mut state = State::new();
+        state.effects.push(Effect { ticks_left: 0, ..Effect::new(EffectKind::Explosion, 2, 0) });
+
+        let value = serde_json::to_value(SavedState::new(state)).unwrap();
+        assert!(matches!(SavedState::from_value(value), Err(LoadError::ExpiredEffect)));
+    }
+
+    #[test]
+    fn json_shape_is_stable() {
+        let mut state = State::new();
+        state.next();
+
+        let json = serde_json::to_value(SavedState::new(state)).unwrap();
+
+        assert_eq!(1, json["schema_version"]);
+        assert_eq!(700, json["state"]["tick_interval_ms"]);
+        assert_eq!(1, json["state"]["tick"]);
+        assert_eq!(START_MONEY, json["state"]["money"]);
+        assert_eq!(
+            serde_json::json!({"LinearRamp": {"from": 4, "to": 1, "over_ticks": 60}}),
+            json["state"]["spawn_rates"]["zombies"][2]
+        );
+        assert_eq!(serde_json::Value::Null, json["state"]["grid"][0]);
+        assert_eq!(9, json["state"]["grid"][2].as_array().unwrap().len());
+    }
+}
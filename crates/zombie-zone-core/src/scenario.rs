@@ -0,0 +1,344 @@
+//! Golden scenarios for the rules: a start board and the boards expected at
+//! chosen ticks, played through `State::next`.
+//!
+//! ```text
+//! # a bullet kills the zombie it flies into
+//! spawn 2 every 4
+//! money 20
+//!
+//! start
+//! -
+//! -
+//! _°Z______
+//! -
+//! -
+//!
+//! tick 1
+//! -
+//! -
+//! __Z(hp=0,dying=3)______
+//! -
+//! -
+//! effect DeathPuff 2 2
+//! ```
+//!
+//! Boards use the text format of `Grid`. Lanes without a `spawn` line spawn
+//! nothing, a curve is one of `off`, `every N`, `ramp FROM TO OVER_TICKS`,
+//! `step BEFORE AFTER AT` and `sine MIN MAX PERIOD`. An `effect KIND LANE FIELD`
+//! line asks for an effect the tick produced, others may come along.
+//! Lanes and fields count from 0, lines starting with `#` are comments.
+
+use std::fmt;
+
+use crate::{EffectKind, Grid, Money, ParseBoardError, SpawnCurve, State, Tick};
+
+const EFFECT_KINDS: [EffectKind; 4] =
+    [EffectKind::Explosion, EffectKind::DeathPuff, EffectKind::MuzzleFlash, EffectKind::HitSpark];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scenario {
+    /// the comment lines at the top of the file, without their `#`
+    pub description: Vec<String>,
+    pub money: Money,
+    pub spawns: Vec<(usize, SpawnCurve)>,
+    pub start: Grid,
+    /// sorted by tick, the first one is after tick 0
+    pub expectations: Vec<Expectation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expectation {
+    pub tick: Tick,
+    pub grid: Grid,
+    /// (kind, lane, field) of effects the tick has to produce
+    pub effects: Vec<(EffectKind, usize, usize)>,
+}
+
+/// the line numbers count from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScenarioError {
+    UnknownLine(usize, String),
+    /// the board of the `start` or `tick` on that line
+    Board(usize, ParseBoardError),
+    /// a `tick` that is not after the one before it
+    TickOrder(usize),
+    MissingStart,
+}
+
+/// where a scenario played out differently than written
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub tick: Tick,
+    pub expected: Grid,
+    pub actual: Grid,
+    pub missing_effects: Vec<(EffectKind, usize, usize)>,
+}
+
+/// a `start` or `tick` line and everything up to the next one
+struct Block<'a> {
+    line: usize,
+    tick: Tick,
+    board: Vec<&'a str>,
+    effects: Vec<(EffectKind, usize, usize)>,
+}
+
+impl Block<'_> {
+    fn grid(&self) -> Result<Grid, ScenarioError> {
+        return self.board.join("\n").parse().map_err(|err| ScenarioError::Board(self.line, err));
+    }
+}
+
+impl Scenario {
+    pub fn parse(text: &str) -> Result<Scenario, ScenarioError> {
+        let mut scenario = Scenario {
+            description: vec![],
+            money: 0,
+            spawns: vec![],
+            start: Grid::default(),
+            expectations: vec![],
+        };
+        let mut blocks: Vec<Block> = vec![];
+
+        for (i, line) in text.lines().enumerate() {
+            let line_no = i + 1;
+            let line = line.trim();
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let unknown = || ScenarioError::UnknownLine(line_no, String::from(line));
+
+            if let Some(comment) = line.strip_prefix('#') {
+                /* only the comments before anything else describe the scenario */
+                if blocks.is_empty() && scenario.spawns.is_empty() && scenario.money == 0 {
+                    scenario.description.push(String::from(comment.trim()));
+                }
+                continue;
+            }
+
+            match (words.as_slice(), blocks.last_mut()) {
+                ([], _) => {}
+                (["start"], None) => blocks.push(Block { line: line_no, tick: 0, board: vec![], effects: vec![] }),
+                (["money", money], None) => scenario.money = money.parse().map_err(|_| unknown())?,
+                (["spawn", lane, curve @ ..], None) => {
+                    let lane = lane.parse().ok().filter(|&lane| lane < scenario.start.len()).ok_or_else(unknown)?;
+                    scenario.spawns.push((lane, parse_curve(curve).ok_or_else(unknown)?));
+                }
+                (_, None) => return Err(unknown()),
+                (["tick", tick], Some(block)) => {
+                    let tick = tick.parse().map_err(|_| unknown())?;
+                    if tick <= block.tick {
+                        return Err(ScenarioError::TickOrder(line_no));
+                    }
+                    blocks.push(Block { line: line_no, tick, board: vec![], effects: vec![] });
+                }
+                (["effect", kind, lane, field], Some(block)) => {
+                    let kind = EFFECT_KINDS.into_iter().find(|known| format!("{:?}", known) == *kind);
+                    match (kind, lane.parse(), field.parse()) {
+                        (Some(kind), Ok(lane), Ok(field)) => block.effects.push((kind, lane, field)),
+                        _ => return Err(unknown()),
+                    }
+                }
+                (_, Some(block)) => block.board.push(line),
+            }
+        }
+
+        let mut blocks = blocks.into_iter();
+        scenario.start = blocks.next().ok_or(ScenarioError::MissingStart)?.grid()?;
+        for block in blocks {
+            scenario.expectations.push(Expectation { tick: block.tick, grid: block.grid()?, effects: block.effects });
+        }
+        return Ok(scenario);
+    }
+
+    /// the state at tick 0
+    pub fn state(&self) -> State {
+        let mut state = State {
+            money: self.money,
+            grid: self.start.clone(),
+            ..State::default()
+        };
+        for lane in 0..state.grid.len() {
+            state.set_spawn_curve(lane, SpawnCurve::Off);
+        }
+        for (lane, curve) in &self.spawns {
+            state.set_spawn_curve(*lane, curve.clone());
+        }
+        return state;
+    }
+
+    /// plays the scenario and stops at the first tick that differs from its expectation
+    pub fn run(&self) -> Result<(), Mismatch> {
+        let mut state = self.state();
+        for expectation in &self.expectations {
+            while state.tick < expectation.tick {
+                state.next();
+            }
+
+            let missing_effects: Vec<(EffectKind, usize, usize)> = expectation
+                .effects
+                .iter()
+                .filter(|(kind, lane, field)| {
+                    !state.new_effects().any(|effect| effect.kind == *kind && effect.lane == *lane && effect.field == *field)
+                })
+                .cloned()
+                .collect();
+            if state.grid != expectation.grid || !missing_effects.is_empty() {
+                return Err(Mismatch {
+                    tick: state.tick,
+                    expected: expectation.grid.clone(),
+                    actual: state.grid.clone(),
+                    missing_effects,
+                });
+            }
+        }
+        return Ok(());
+    }
+
+    /// takes every expected board from what the scenario actually plays out to
+    pub fn bless(&mut self) {
+        let mut state = self.state();
+        for expectation in &mut self.expectations {
+            while state.tick < expectation.tick {
+                state.next();
+            }
+            expectation.grid = state.grid.clone();
+        }
+    }
+}
+
+fn parse_curve(words: &[&str]) -> Option<SpawnCurve> {
+    let numbers: Vec<Tick> = words[1.min(words.len())..].iter().map(|word| word.parse().ok()).collect::<Option<_>>()?;
+    return match (words.first(), numbers.as_slice()) {
+        (Some(&"off"), []) => Some(SpawnCurve::Off),
+        (Some(&"every"), [interval]) => Some(SpawnCurve::Constant(*interval)),
+        (Some(&"ramp"), [from, to, over_ticks]) => Some(SpawnCurve::LinearRamp { from: *from, to: *to, over_ticks: *over_ticks }),
+        (Some(&"step"), [before, after, at]) => Some(SpawnCurve::Step { before: *before, after: *after, at: *at }),
+        (Some(&"sine"), [min, max, period]) => Some(SpawnCurve::Sine { min: *min, max: *max, period: *period }),
+        _ => None,
+    };
+}
+
+fn curve_text(curve: &SpawnCurve) -> String {
+    return match curve {
+        SpawnCurve::Off => String::from("off"),
+        SpawnCurve::Constant(interval) => format!("every {}", interval),
+        SpawnCurve::LinearRamp { from, to, over_ticks } => format!("ramp {} {} {}", from, to, over_ticks),
+        SpawnCurve::Step { before, after, at } => format!("step {} {} {}", before, after, at),
+        SpawnCurve::Sine { min, max, period } => format!("sine {} {} {}", min, max, period),
+    };
+}
+
+/// the scenario as a file `Scenario::parse` reads back
+impl fmt::Display for Scenario {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in &self.description {
+            writeln!(f, "# {}", line)?;
+        }
+        for (lane, curve) in &self.spawns {
+            writeln!(f, "spawn {} {}", lane, curve_text(curve))?;
+        }
+        if self.money != 0 {
+            writeln!(f, "money {}", self.money)?;
+        }
+        if !self.description.is_empty() || !self.spawns.is_empty() || self.money != 0 {
+            writeln!(f)?;
+        }
+
+        writeln!(f, "start\n{}", self.start)?;
+        for expectation in &self.expectations {
+            writeln!(f, "\ntick {}\n{}", expectation.tick, expectation.grid)?;
+            for (kind, lane, field) in &expectation.effects {
+                writeln!(f, "effect {:?} {} {}", kind, lane, field)?;
+            }
+        }
+        return Ok(());
+    }
+}
+
+impl Mismatch {
+    /// every lane that differs, with a `^` under the first char that does
+    pub fn diff(&self) -> String {
+        let mut diff = format!("tick {}\n", self.tick);
+        let expected = self.expected.to_string();
+        let actual = self.actual.to_string();
+
+        for (i, (expected, actual)) in expected.lines().zip(actual.lines()).enumerate() {
+            if expected == actual {
+                continue;
+            }
+            let same = expected.chars().zip(actual.chars()).take_while(|(a, b)| a == b).count();
+            diff += &format!("  lane {} expected  {}\n", i, expected);
+            diff += &format!("         actual    {}\n", actual);
+            diff += &format!("                   {}^\n", " ".repeat(same));
+        }
+        for (kind, lane, field) in &self.missing_effects {
+            diff += &format!("  missing effect {:?} on lane {} field {}\n", kind, lane, field);
+        }
+        return diff;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCENARIO: &str = "# a bullet kills the zombie it flies into
+spawn 4 every 4
+money 20
+
+start
+-
+-
+_°Z______
+-
+-
+
+tick 1
+-
+-
+__Z(hp=0,dying=3)______
+-
+-
+effect DeathPuff 2 2
+";
+
+    #[test]
+    fn parses_and_prints_back() {
+        let scenario = Scenario::parse(SCENARIO).unwrap();
+        assert_eq!(vec![String::from("a bullet kills the zombie it flies into")], scenario.description);
+        assert_eq!(vec![(4, SpawnCurve::Constant(4))], scenario.spawns);
+        assert_eq!(vec![(EffectKind::DeathPuff, 2, 2)], scenario.expectations[0].effects);
+        assert_eq!(SCENARIO, scenario.to_string());
+        assert_eq!(Ok(()), scenario.run());
+    }
+
+    #[test]
+    fn mismatch_shows_the_differing_lane() {
+        let scenario = Scenario::parse(&SCENARIO.replace("dying=3", "dying=4").replace("DeathPuff", "HitSpark")).unwrap();
+
+        let mismatch = scenario.run().unwrap_err();
+        let expected = [
+            "tick 1",
+            "  lane 2 expected  __Z(hp=0,dying=4)______",
+            "         actual    __Z(hp=0,dying=3)______",
+            "                                  ^",
+            "  missing effect HitSpark on lane 2 field 2",
+            "",
+        ];
+        assert_eq!(expected.join("\n"), mismatch.diff());
+    }
+
+    #[test]
+    fn bless_takes_the_actual_boards() {
+        let mut scenario = Scenario::parse(&SCENARIO.replace("dying=3", "dying=4")).unwrap();
+        scenario.bless();
+        assert_eq!(SCENARIO, scenario.to_string());
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        assert_eq!(Err(ScenarioError::MissingStart), Scenario::parse("money 3"));
+        assert_eq!(Err(ScenarioError::UnknownLine(1, String::from("spawn 9 every 2"))), Scenario::parse("spawn 9 every 2"));
+        assert_eq!(Err(ScenarioError::UnknownLine(1, String::from("spawn 2 wave"))), Scenario::parse("spawn 2 wave"));
+        assert_eq!(Err(ScenarioError::TickOrder(7)), Scenario::parse("start\n-\n-\n-\n-\n-\ntick 0"));
+        assert!(matches!(Scenario::parse("start\n-\n-\nZ\n-\n-"), Err(ScenarioError::Board(1, _))));
+    }
+}
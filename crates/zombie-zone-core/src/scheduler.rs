@@ -0,0 +1,165 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// where the scheduler gets the time from, in milliseconds since any fixed point
+pub trait Clock {
+    fn now_ms(&self) -> f64;
+}
+
+/// A clock that only moves when told to. Clones share the same time, so a
+/// test can keep one and hand the other to a `Scheduler`.
+#[derive(Debug, Default, Clone)]
+pub struct ManualClock(Rc<Cell<f64>>);
+
+impl ManualClock {
+    pub fn advance(&self, ms: f64) {
+        self.0.set(self.0.get() + ms);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_ms(&self) -> f64 {
+        return self.0.get();
+    }
+}
+
+/// Decides once per rendered frame how many ticks are due, so the game runs
+/// at a fixed tick rate no matter how often frames come.
+/// Ticks missed by slow or dropped frames are caught up, at most
+/// `max_catch_up_ticks` per frame. Time beyond that is dropped, so a long
+/// stall (e.g. a background tab) can not snowball into ever longer frames.
+pub struct Scheduler<C: Clock> {
+    clock: C,
+    tick_interval_ms: f64,
+    max_catch_up_ticks: u32,
+    /// None until the first frame and while paused
+    last_frame_ms: Option<f64>,
+    /// time that passed but did not make up a whole tick yet
+    pending_ms: f64,
+}
+
+impl<C: Clock> Scheduler<C> {
+    pub const DEFAULT_MAX_CATCH_UP_TICKS: u32 = 5;
+
+    pub fn new(clock: C, tick_interval_ms: u16) -> Scheduler<C> {
+        return Scheduler {
+            clock,
+            tick_interval_ms: tick_interval_ms.max(1) as f64,
+            max_catch_up_ticks: Scheduler::<C>::DEFAULT_MAX_CATCH_UP_TICKS,
+            last_frame_ms: None,
+            pending_ms: 0.0,
+        };
+    }
+
+    pub fn with_max_catch_up_ticks(mut self, ticks: u32) -> Scheduler<C> {
+        self.max_catch_up_ticks = ticks.max(1);
+        return self;
+    }
+
+    /// Call once per frame, returns how many times to call `State::next`
+    /// before drawing it. The first frame only starts the clock.
+    pub fn frame(&mut self) -> u32 {
+        let now = self.clock.now_ms();
+        let last = self.last_frame_ms.replace(now);
+        let elapsed = match last {
+            Some(last) => (now - last).max(0.0),
+            None => return 0,
+        };
+
+        self.pending_ms += elapsed;
+        let due = (self.pending_ms / self.tick_interval_ms) as u32;
+        if due > self.max_catch_up_ticks {
+            self.pending_ms = 0.0;
+            return self.max_catch_up_ticks;
+        }
+
+        self.pending_ms -= due as f64 * self.tick_interval_ms;
+        return due;
+    }
+
+    /// How far the game is on its way to the next tick, from 0 right after a
+    /// tick to just below 1. Views use it to draw entities between fields.
+    pub fn interpolation(&self) -> f64 {
+        return (self.pending_ms / self.tick_interval_ms).clamp(0.0, 1.0);
+    }
+
+    /// time passing until the next frame after this does not count
+    pub fn pause(&mut self) {
+        self.last_frame_ms = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ticks_at_a_fixed_rate_whatever_the_frame_rate() {
+        let clock = ManualClock::default();
+        let mut scheduler = Scheduler::new(clock.clone(), 100);
+        assert_eq!(0, scheduler.frame());
+
+        let mut ticks = 0;
+        /* a second at 50 frames per second */
+        for _ in 0..50 {
+            clock.advance(20.0);
+            ticks += scheduler.frame();
+        }
+
+        assert_eq!(10, ticks);
+    }
+
+    #[test]
+    fn interpolation_follows_the_time_between_ticks() {
+        let clock = ManualClock::default();
+        let mut scheduler = Scheduler::new(clock.clone(), 100);
+        scheduler.frame();
+
+        clock.advance(25.0);
+        assert_eq!(0, scheduler.frame());
+        assert_eq!(0.25, scheduler.interpolation());
+
+        clock.advance(100.0);
+        assert_eq!(1, scheduler.frame());
+        assert_eq!(0.25, scheduler.interpolation());
+    }
+
+    #[test]
+    fn dropped_frames_are_caught_up() {
+        let clock = ManualClock::default();
+        let mut scheduler = Scheduler::new(clock.clone(), 100);
+        scheduler.frame();
+
+        clock.advance(350.0);
+        assert_eq!(3, scheduler.frame());
+        assert_eq!(0.5, scheduler.interpolation());
+    }
+
+    #[test]
+    fn catching_up_is_capped() {
+        let clock = ManualClock::default();
+        let mut scheduler = Scheduler::new(clock.clone(), 100).with_max_catch_up_ticks(4);
+        scheduler.frame();
+
+        clock.advance(60_000.0);
+        assert_eq!(4, scheduler.frame());
+
+        /* the stall is forgotten, the next frames run at the normal rate */
+        clock.advance(100.0);
+        assert_eq!(1, scheduler.frame());
+    }
+
+    #[test]
+    fn paused_time_does_not_count() {
+        let clock = ManualClock::default();
+        let mut scheduler = Scheduler::new(clock.clone(), 100);
+        scheduler.frame();
+
+        scheduler.pause();
+        clock.advance(1000.0);
+        assert_eq!(0, scheduler.frame());
+
+        clock.advance(100.0);
+        assert_eq!(1, scheduler.frame());
+    }
+}
@@ -0,0 +1,471 @@
+//! Level logic written in Rhai, for levels that need more than a spawn curve.
+//!
+//! A level script defines any of these hooks, each gets called with `this`
+//! bound to a map that keeps its contents for the whole level:
+//!
+//! ```rhai
+//! fn on_tick(grid) {}               // after every tick
+//! fn on_spawn(grid, lane) {}        // a zombie entered `lane` through the spawn curve
+//! fn on_kill(grid, lane, field) {}  // a zombie got killed on that field
+//! ```
+//!
+//! `grid` is a read only view of the state after the tick:
+//!
+//! ```rhai
+//! grid.tick                // the tick that just happened
+//! grid.lanes               // how many lanes there are, counting missing ones
+//! grid.has_lane(lane)
+//! grid.fields(lane)        // 0 for a missing lane
+//! grid.zombies(lane)       // zombies still in the game, every one of a stack
+//! grid.turrets(lane)
+//! grid.kinds(lane, field)  // the kinds on the field, bottom first
+//! ```
+//!
+//! Hooks change the game only through `place(lane, kind)`, `place_at(lane, field, kind)` and
+//! `effect(lane, field, kind)`, which get queued and applied once every hook
+//! of the tick ran. The script cannot reach anything else, and every hook call
+//! is cut off after `ScriptLimits::max_operations` operations. A hook that
+//! fails loses the commands it queued, the other hooks of the tick still run.
+
+use std::{cell::RefCell, rc::Rc};
+
+use rhai::{module_resolvers::DummyModuleResolver, CallFnOptions, Dynamic, Engine, EvalAltResult, Map, ParseError, Scope, AST};
+
+use crate::{ActionError, Effect, EffectKind, Entity, Field, Grid, Registry, Role, State, Tick};
+
+/// keeps a broken or hostile script from freezing the game
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptLimits {
+    /// per hook call
+    pub max_operations: u64,
+    pub max_call_levels: usize,
+    pub max_string_size: usize,
+    /// also caps the entries of a map
+    pub max_array_size: usize,
+}
+
+impl Default for ScriptLimits {
+    fn default() -> ScriptLimits {
+        ScriptLimits {
+            max_operations: 10_000,
+            max_call_levels: 16,
+            max_string_size: 1_000,
+            max_array_size: 1_000,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ScriptError {
+    Parse(ParseError),
+    /// includes running out of operations, the commands of the hooks that did not fail still got applied
+    Runtime(Box<EvalAltResult>),
+    /// a queued command did not fit the grid, the other commands still got applied
+    Rejected(ScriptCommand, ActionError),
+}
+
+/// a change to the game queued by a hook
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptCommand {
+    Spawn { lane: usize, field: Option<usize>, kind: String },
+    Effect { lane: usize, field: usize, kind: EffectKind },
+}
+
+/// what a hook sees of the state
+#[derive(Clone)]
+pub struct GridView {
+    tick: Tick,
+    grid: Grid,
+}
+
+pub struct LevelScript {
+    engine: Engine,
+    ast: AST,
+    /// `this` of every hook
+    memory: Dynamic,
+    commands: Rc<RefCell<Vec<ScriptCommand>>>,
+    registry: Registry,
+}
+
+impl LevelScript {
+    pub fn new(source: &str, limits: ScriptLimits) -> Result<LevelScript, ScriptError> {
+        let registry = Registry::builtin();
+        let commands = Rc::new(RefCell::new(vec![]));
+        let engine = LevelScript::engine(&limits, &registry, &commands);
+        let ast = engine.compile(source).map_err(ScriptError::Parse)?;
+
+        /* top level statements run once, hooks only see what they stored in `this` */
+        engine.run_ast(&ast).map_err(ScriptError::Runtime)?;
+        commands.borrow_mut().clear();
+
+        return Ok(LevelScript {
+            engine,
+            ast,
+            memory: Dynamic::from_map(Map::new()),
+            commands,
+            registry,
+        });
+    }
+
+    fn engine(limits: &ScriptLimits, registry: &Registry, commands: &Rc<RefCell<Vec<ScriptCommand>>>) -> Engine {
+        let mut engine = Engine::new();
+        /* outside the browser `Engine::new` would load any `.rhai` file on disk through `import` */
+        engine.set_module_resolver(DummyModuleResolver::new());
+        engine
+            .set_max_operations(limits.max_operations)
+            .set_max_call_levels(limits.max_call_levels)
+            .set_max_string_size(limits.max_string_size)
+            .set_max_array_size(limits.max_array_size)
+            .set_max_map_size(limits.max_array_size)
+            .disable_symbol("eval")
+            .disable_symbol("import")
+            .on_print(|_| {})
+            .on_debug(|_, _, _| {});
+
+        engine
+            .register_type_with_name::<GridView>("Grid")
+            .register_get("tick", |view: &mut GridView| view.tick as i64)
+            .register_get("lanes", |view: &mut GridView| view.grid.len() as i64)
+            .register_fn("has_lane", |view: &mut GridView, lane: i64| view.lane(lane).is_some())
+            .register_fn("fields", |view: &mut GridView, lane: i64| {
+                view.lane(lane).map_or(0, |lane| lane.len() as i64)
+            })
+            .register_fn("zombies", |view: &mut GridView, lane: i64| {
+                view.count(lane, |entity| entity.behavior().is_target())
+            })
+            .register_fn("turrets", |view: &mut GridView, lane: i64| {
+                view.count(lane, |entity| entity.behavior().role() == Role::Defender)
+            })
+            .register_fn("kinds", |view: &mut GridView, lane: i64, field: i64| {
+                let kinds = view.field(lane, field).map(|field| {
+                    field
+                        .iter()
+                        .map(|entity| Dynamic::from(entity.behavior().kind().to_string()))
+                        .collect::<rhai::Array>()
+                });
+                kinds.unwrap_or_default()
+            });
+
+        let queue = commands.clone();
+        let known = registry.clone();
+        engine.register_fn("place", move |lane: i64, kind: &str| -> Result<(), Box<EvalAltResult>> {
+            queue.borrow_mut().push(ScriptCommand::Spawn {
+                lane: index(lane)?,
+                field: None,
+                kind: known_kind(&known, kind)?,
+            });
+            return Ok(());
+        });
+
+        let queue = commands.clone();
+        let known = registry.clone();
+        engine.register_fn(
+            "place_at",
+            move |lane: i64, field: i64, kind: &str| -> Result<(), Box<EvalAltResult>> {
+                queue.borrow_mut().push(ScriptCommand::Spawn {
+                    lane: index(lane)?,
+                    field: Some(index(field)?),
+                    kind: known_kind(&known, kind)?,
+                });
+                return Ok(());
+            },
+        );
+
+        let queue = commands.clone();
+        engine.register_fn(
+            "effect",
+            move |lane: i64, field: i64, kind: &str| -> Result<(), Box<EvalAltResult>> {
+                let kind = match kind {
+                    "explosion" => EffectKind::Explosion,
+                    "death_puff" => EffectKind::DeathPuff,
+                    "muzzle_flash" => EffectKind::MuzzleFlash,
+                    "hit_spark" => EffectKind::HitSpark,
+                    _ => return Err(format!("unknown effect '{}'", kind).into()),
+                };
+                queue.borrow_mut().push(ScriptCommand::Effect {
+                    lane: index(lane)?,
+                    field: index(field)?,
+                    kind,
+                });
+                return Ok(());
+            },
+        );
+
+        return engine;
+    }
+
+    /// advances the state by one tick and runs the hooks on the result
+    pub fn next(&mut self, state: &mut State) -> Result<(), ScriptError> {
+        state.next();
+
+        let view = GridView {
+            tick: state.tick,
+            grid: state.grid.clone(),
+        };
+
+        /* a failing hook is reported once every hook ran and the commands of the others got applied */
+        let mut results = vec![];
+        for lane in 0..state.grid.len() {
+            if state.spawn_rates.last_zombie_spawns[lane] == state.tick {
+                results.push(self.call("on_spawn", (view.clone(), lane as i64)));
+            }
+        }
+
+        let kills: Vec<(usize, usize)> = state
+            .new_effects()
+            .filter(|effect| effect.kind == EffectKind::DeathPuff)
+            .map(|effect| (effect.lane, effect.field))
+            .collect();
+        for (lane, field) in kills {
+            results.push(self.call("on_kill", (view.clone(), lane as i64, field as i64)));
+        }
+
+        results.push(self.call("on_tick", (view,)));
+
+        let applied = self.apply(state);
+        return results.into_iter().find(|result| result.is_err()).unwrap_or(applied);
+    }
+
+    fn call(&mut self, hook: &str, args: impl rhai::FuncArgs) -> Result<(), ScriptError> {
+        if !self.ast.iter_functions().any(|function| function.name == hook) {
+            return Ok(());
+        }
+
+        let queued = self.commands.borrow().len();
+        let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(&mut self.memory);
+        let result = self
+            .engine
+            .call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &self.ast, hook, args);
+        if let Err(error) = result {
+            /* a hook that failed halfway must not leave half of its commands behind, those of earlier hooks stay */
+            self.commands.borrow_mut().truncate(queued);
+            return Err(ScriptError::Runtime(error));
+        }
+        return Ok(());
+    }
+
+    /// applies the queued commands in order and reports the first one that got rejected
+    fn apply(&mut self, state: &mut State) -> Result<(), ScriptError> {
+        let mut rejected = None;
+
+        for command in self.commands.take() {
+            let result = match &command {
+                ScriptCommand::Spawn { lane, field, kind } => {
+                    let entity = self.registry.spawn(kind, state.tick).unwrap();
+                    let field = field.unwrap_or_else(|| spawn_field(&state.grid, *lane, &entity));
+                    state.place_entity(*lane, field, entity)
+                }
+                ScriptCommand::Effect { lane, field, kind } => match state.grid.get(*lane) {
+                    Some(Some(known)) if *field < known.len() => {
+                        state.effects.push(Effect::new(*kind, *lane, *field));
+                        Ok(())
+                    }
+                    Some(Some(_)) => Err(ActionError::NoField),
+                    _ => Err(ActionError::NoLane),
+                },
+            };
+
+            match result {
+                Err(error) if rejected.is_none() => rejected = Some(ScriptError::Rejected(command, error)),
+                _ => {}
+            }
+        }
+
+        return match rejected {
+            Some(error) => Err(error),
+            None => Ok(()),
+        };
+    }
+}
+
+impl GridView {
+    fn lane(&self, lane: i64) -> Option<&crate::Lane> {
+        self.grid.get(usize::try_from(lane).ok()?)?.as_deref()
+    }
+
+    fn field(&self, lane: i64, field: i64) -> Option<&Field> {
+        self.lane(lane)?.get(usize::try_from(field).ok()?)
+    }
+
+    /// entities, not fields, a stack of three zombies counts 3
+    fn count(&self, lane: i64, matches: fn(&Entity) -> bool) -> i64 {
+        let entities = |lane: &crate::Lane| lane.iter().flat_map(|field| field.iter()).filter(|entity| matches(entity)).count();
+        self.lane(lane).map_or(0, |lane| entities(lane) as i64)
+    }
+}
+
+/// attackers enter at the end of the lane, everything else at its start
+fn spawn_field(grid: &Grid, lane: usize, entity: &Entity) -> usize {
+    let fields = grid.get(lane).and_then(|lane| lane.as_ref()).map_or(0, |lane| lane.len());
+    if entity.behavior().role() == Role::Attacker {
+        return fields.saturating_sub(1);
+    }
+    return 0;
+}
+
+fn index(value: i64) -> Result<usize, Box<EvalAltResult>> {
+    usize::try_from(value).map_err(|_| format!("{} is not a valid index", value).into())
+}
+
+fn known_kind(registry: &Registry, kind: &str) -> Result<String, Box<EvalAltResult>> {
+    match registry.get(kind) {
+        Some(kind) => Ok(kind.name.to_string()),
+        None => Err(format!("unknown entity kind '{}'", kind).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SpawnCurve, Turret, TurretKind, Zombie, LOCAL_PLAYER, ZOMBIE_HEALTH};
+
+    fn quiet_state() -> State {
+        let mut state = State::new();
+        state.set_spawn_curve(2, SpawnCurve::Off);
+        return state;
+    }
+
+    #[test]
+    fn horde_spawns_once_when_lane_has_no_turrets() {
+        let source = r#"
+            fn on_tick(grid) {
+                if grid.turrets(2) == 0 && !(this.horde ?? false) {
+                    this.horde = true;
+                    place(2, "zombie");
+                    place_at(2, 7, "zombie");
+                    effect(2, 8, "explosion");
+                }
+            }
+        "#;
+        let mut script = LevelScript::new(source, ScriptLimits::default()).unwrap();
+        let mut state = quiet_state();
+
+        script.next(&mut state).unwrap();
+        let lane = state.grid[2].as_ref().unwrap();
+        assert_eq!(1, lane[8].len());
+        assert_eq!(1, lane[7].len());
+        assert_eq!(1, state.effects_at(2, 8).count());
+
+        script.next(&mut state).unwrap();
+        let zombies: usize = state.grid[2].as_ref().unwrap().iter().map(|field| field.len()).sum();
+        assert_eq!(2, zombies);
+    }
+
+    #[test]
+    fn kill_and_spawn_hooks_see_where_it_happened() {
+        let source = r#"
+            fn on_spawn(grid, lane) { effect(lane, 3, "hit_spark"); }
+            fn on_kill(grid, lane, field) {
+                this.kills = (this.kills ?? 0) + 1;
+                effect(lane, field + 4, "explosion");
+            }
+        "#;
+        let mut script = LevelScript::new(source, ScriptLimits::default()).unwrap();
+        let mut state = quiet_state();
+        state.set_spawn_curve(2, SpawnCurve::Constant(1));
+        let turret = Turret { cooldown: 0, ..Turret::new(TurretKind::Rapid, LOCAL_PLAYER) };
+        state.place_entity(2, 0, Entity::Turret(turret)).unwrap();
+        state.place_entity(2, 1, Entity::Zombie(Zombie::new(0, ZOMBIE_HEALTH))).unwrap();
+
+        script.next(&mut state).unwrap();
+
+        /* the turret killed the zombie on field 1 while another one spawned */
+        let effect_at = |field: usize| state.effects_at(2, field).map(|effect| effect.kind).collect::<Vec<_>>();
+        assert_eq!(vec![EffectKind::HitSpark], effect_at(3));
+        assert_eq!(vec![EffectKind::Explosion], effect_at(5));
+        let kills = script.memory.clone().cast::<Map>()["kills"].as_int();
+        assert_eq!(Ok(1), kills);
+    }
+
+    #[test]
+    fn endless_loop_runs_out_of_operations() {
+        let mut script = LevelScript::new("fn on_tick(grid) { loop {} }", ScriptLimits::default()).unwrap();
+        let mut state = quiet_state();
+
+        let result = script.next(&mut state);
+        assert!(matches!(result, Err(ScriptError::Runtime(error)) if matches!(*error, EvalAltResult::ErrorTooManyOperations(_))));
+        /* the tick itself still happened */
+        assert_eq!(1, state.tick);
+    }
+
+    #[test]
+    fn grid_counts_every_entity_of_a_stack() {
+        let source = r#"fn on_tick(grid) { this.zombies = grid.zombies(2); this.turrets = grid.turrets(2); }"#;
+        let mut script = LevelScript::new(source, ScriptLimits::default()).unwrap();
+        let mut state = quiet_state();
+        for _ in 0..3 {
+            state.place_entity(2, 5, Entity::Zombie(Zombie::new(0, ZOMBIE_HEALTH))).unwrap();
+        }
+        state.place_entity(2, 0, Entity::Turret(Turret::new(TurretKind::Basic, LOCAL_PLAYER))).unwrap();
+
+        script.next(&mut state).unwrap();
+        let memory = script.memory.clone().cast::<Map>();
+        assert_eq!(Ok(3), memory["zombies"].as_int());
+        assert_eq!(Ok(1), memory["turrets"].as_int());
+    }
+
+    #[test]
+    fn failing_hook_only_drops_its_own_commands() {
+        let source = r#"
+            fn on_spawn(grid, lane) {
+                effect(lane, 3, "hit_spark");
+                throw "broken";
+            }
+            fn on_tick(grid) { effect(2, 5, "explosion"); }
+        "#;
+        let mut script = LevelScript::new(source, ScriptLimits::default()).unwrap();
+        let mut state = quiet_state();
+        state.set_spawn_curve(2, SpawnCurve::Constant(1));
+
+        let result = script.next(&mut state);
+        assert!(matches!(result, Err(ScriptError::Runtime(_))));
+        /* on_tick still ran after on_spawn failed */
+        assert_eq!(0, state.effects_at(2, 3).count());
+        assert_eq!(1, state.effects_at(2, 5).count());
+    }
+
+    #[test]
+    fn unknown_kinds_and_rejected_spawns_are_reported() {
+        let mut script = LevelScript::new(r#"fn on_tick(grid) { place(2, "ghost"); }"#, ScriptLimits::default()).unwrap();
+        assert!(matches!(script.next(&mut quiet_state()), Err(ScriptError::Runtime(_))));
+
+        let source = r#"fn on_tick(grid) { place(2, "turret"); place(2, "turret"); place(2, "bullet"); }"#;
+        let mut script = LevelScript::new(source, ScriptLimits::default()).unwrap();
+        let mut state = quiet_state();
+        let result = script.next(&mut state);
+        assert!(matches!(result, Err(ScriptError::Rejected(ScriptCommand::Spawn { .. }, ActionError::Occupied))));
+        /* the bullet after the rejected turret still got placed */
+        assert_eq!(2, state.grid[2].as_ref().unwrap()[0].len());
+
+        assert!(matches!(LevelScript::new("fn on_tick(", ScriptLimits::default()), Err(ScriptError::Parse(_))));
+    }
+
+    #[test]
+    fn scripts_cannot_import_files() {
+        let dir = std::env::temp_dir().join(format!("zz-script-import-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let module = dir.join("other.rhai");
+        std::fs::write(&module, "fn horde() { 1 }").unwrap();
+
+        let source = format!(r#"import "{}" as other; fn on_tick(grid) {{ other::horde(); }}"#, module.with_extension("").display());
+        let result = LevelScript::new(&source, ScriptLimits::default());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(result, Err(ScriptError::Parse(_))));
+    }
+
+    #[test]
+    fn effects_off_the_grid_are_rejected() {
+        let source = r#"fn on_tick(grid) { effect(2, 9, "explosion"); effect(0, 3, "explosion"); effect(2, 8, "hit_spark"); }"#;
+        let mut script = LevelScript::new(source, ScriptLimits::default()).unwrap();
+        let mut state = quiet_state();
+
+        let result = script.next(&mut state);
+        assert!(matches!(result, Err(ScriptError::Rejected(ScriptCommand::Effect { field: 9, .. }, ActionError::NoField))));
+        assert_eq!(1, state.effects.len());
+        assert_eq!(1, state.effects_at(2, 8).count());
+
+        let mut script = LevelScript::new(r#"fn on_tick(grid) { effect(0, 3, "explosion"); }"#, ScriptLimits::default()).unwrap();
+        let result = script.next(&mut state);
+        assert!(matches!(result, Err(ScriptError::Rejected(ScriptCommand::Effect { lane: 0, .. }, ActionError::NoLane))));
+    }
+}
@@ -0,0 +1,390 @@
+//! A compact text format for boards, for tests, logs and bug reports.
+//!
+//! A lane is one line of fields, `T______ZZ`. A grid is one lane per line,
+//! a lane that does not exist is written `-`. Every field is one of
+//! - `_` for an empty field
+//! - a single entity, `Z` zombie, `T` turret, `°` bullet
+//! - a stack of entities in brackets, bottom first, `[ZZ]`
+//!
+//! An entity that differs from a freshly spawned one lists the difference
+//! in parentheses, `Z(t=3,hp=1,stunned=5)`. Symbols and attributes come
+//! from the kinds in `Registry::builtin`, for the built-in kinds they are
+//! - zombie: `t` last moved, `hp`, one of `attacking`, `blocked`, `stunned`, `dying`
+//! - turret: `rapid`, `tier`, `owner`, `cd` cooldown
+//! - bullet: `t` last moved, `dmg`
+//!
+//! Whitespace between fields is ignored, so a lane can be spaced out.
+
+use std::{fmt, str::FromStr, sync::OnceLock};
+
+use crate::{Entity, Field, Grid, Lane, Registry};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseBoardError {
+    /// a grid has 5 lanes, counting the `-` ones
+    LaneCount(usize),
+    /// a lane has 9 fields
+    FieldCount(usize),
+    UnknownSymbol(char),
+    /// an attribute the entity does not have, or one that contradicts an earlier one
+    BadAttribute(String),
+    /// a value that is missing or not a number
+    BadValue(String),
+    /// a `[` or `(` that never gets closed
+    Unclosed(char),
+    /// `[]`, an empty field is written `_`
+    EmptyStack,
+    /// a stack that breaks the rules documented on `Field`
+    Inconsistent(String),
+    /// the error and the lane it is in, counting from 0
+    InLane(usize, Box<ParseBoardError>),
+}
+
+/// walks through the text one char at a time
+struct Reader<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn peek(&self) -> Option<char> {
+        return self.text[self.pos..].chars().next();
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        return Some(c);
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    /// everything up to `close`, which gets consumed
+    fn until(&mut self, open: char, close: char) -> Result<&'a str, ParseBoardError> {
+        let rest = &self.text[self.pos..];
+        let end = rest.find(close).ok_or(ParseBoardError::Unclosed(open))?;
+        self.pos += end + close.len_utf8();
+        return Ok(&rest[..end]);
+    }
+
+    fn entity(&mut self) -> Result<Entity, ParseBoardError> {
+        let symbol = self.bump().ok_or(ParseBoardError::FieldCount(0))?;
+        let mut attributes = match self.peek() {
+            Some('(') => {
+                self.bump();
+                Attributes::parse(self.until('(', ')')?)
+            }
+            _ => Attributes(vec![]),
+        };
+
+        let kind = kinds().by_symbol(symbol).ok_or(ParseBoardError::UnknownSymbol(symbol))?;
+        let entity = (kind.parse)(&mut attributes)?;
+        attributes.finish()?;
+        return Ok(entity);
+    }
+
+    fn field(&mut self) -> Result<Field, ParseBoardError> {
+        let mut field = Field::default();
+        match self.peek() {
+            Some('_') => {
+                self.bump();
+            }
+            Some('[') => {
+                self.bump();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(']') => break,
+                        Some(_) => field.push_back(self.entity()?),
+                        None => return Err(ParseBoardError::Unclosed('[')),
+                    }
+                }
+                self.bump();
+                if field.is_empty() {
+                    return Err(ParseBoardError::EmptyStack);
+                }
+            }
+            _ => field.push_back(self.entity()?),
+        }
+
+        if !field.is_consistent() {
+            return Err(ParseBoardError::Inconsistent(field.to_string()));
+        }
+        return Ok(field);
+    }
+}
+
+/// the kinds the text format knows, the built-in ones
+fn kinds() -> &'static Registry {
+    static KINDS: OnceLock<Registry> = OnceLock::new();
+    return KINDS.get_or_init(Registry::builtin);
+}
+
+/// The `key=value` and lone `key` attributes of an entity, taken out one by
+/// one by `EntityKind::parse`. Whatever it leaves is an error.
+pub struct Attributes<'a>(Vec<(&'a str, Option<&'a str>)>);
+
+impl<'a> Attributes<'a> {
+    fn parse(text: &'a str) -> Attributes<'a> {
+        let attributes = text
+            .split(',')
+            .map(str::trim)
+            .filter(|attribute| !attribute.is_empty())
+            .map(|attribute| match attribute.split_once('=') {
+                Some((key, value)) => (key.trim(), Some(value.trim())),
+                None => (attribute, None),
+            })
+            .collect();
+        return Attributes(attributes);
+    }
+
+    fn take(&mut self, key: &str) -> Option<Option<&'a str>> {
+        let i = self.0.iter().position(|(known, _)| *known == key)?;
+        return Some(self.0.remove(i).1);
+    }
+
+    /// `key=number`, `None` without the key
+    pub fn number<T: FromStr>(&mut self, key: &str) -> Result<Option<T>, ParseBoardError> {
+        return match self.take(key) {
+            None => Ok(None),
+            Some(value) => match value.and_then(|value| value.parse().ok()) {
+                Some(number) => Ok(Some(number)),
+                None => Err(ParseBoardError::BadValue(format!("{}={}", key, value.unwrap_or("")))),
+            },
+        };
+    }
+
+    /// a lone `key`
+    pub fn flag(&mut self, key: &str) -> Result<bool, ParseBoardError> {
+        return match self.take(key) {
+            None => Ok(false),
+            Some(None) => Ok(true),
+            Some(Some(value)) => Err(ParseBoardError::BadValue(format!("{}={}", key, value))),
+        };
+    }
+
+    /// an attribute nobody took does not belong to the entity
+    fn finish(self) -> Result<(), ParseBoardError> {
+        return match self.0.first() {
+            Some((key, _)) => Err(ParseBoardError::BadAttribute(key.to_string())),
+            None => Ok(()),
+        };
+    }
+}
+
+/// every field in `text`, however many there are
+fn fields(text: &str) -> Result<Vec<Field>, ParseBoardError> {
+    let mut reader = Reader { text, pos: 0 };
+    let mut fields = vec![];
+    loop {
+        reader.skip_whitespace();
+        if reader.peek().is_none() {
+            return Ok(fields);
+        }
+        fields.push(reader.field()?);
+    }
+}
+
+impl fmt::Display for Entity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = match kinds().get(self.behavior().kind()) {
+            Some(kind) => kind.symbol,
+            None => return Err(fmt::Error),
+        };
+        let attributes = self.behavior().text_attributes();
+        if attributes.is_empty() {
+            return write!(f, "{}", symbol);
+        }
+        return write!(f, "{}({})", symbol, attributes.join(","));
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.len() {
+            0 => return write!(f, "_"),
+            1 => return write!(f, "{}", self[0]),
+            _ => {}
+        }
+        write!(f, "[")?;
+        for entity in self.iter() {
+            write!(f, "{}", entity)?;
+        }
+        return write!(f, "]");
+    }
+}
+
+impl fmt::Display for Lane {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for field in self.iter() {
+            write!(f, "{}", field)?;
+        }
+        return Ok(());
+    }
+}
+
+/// one lane per line, without a newline after the last one
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, lane) in self.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            match lane {
+                Some(lane) => write!(f, "{}", lane)?,
+                None => write!(f, "-")?,
+            }
+        }
+        return Ok(());
+    }
+}
+
+impl fmt::Debug for Lane {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return f.debug_tuple("Lane").field(&self.to_string()).finish();
+    }
+}
+
+impl fmt::Debug for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lanes: Vec<String> = self.to_string().lines().map(String::from).collect();
+        return f.debug_tuple("Grid").field(&lanes).finish();
+    }
+}
+
+impl FromStr for Entity {
+    type Err = ParseBoardError;
+
+    fn from_str(text: &str) -> Result<Entity, ParseBoardError> {
+        let mut reader = Reader { text: text.trim(), pos: 0 };
+        let entity = reader.entity()?;
+        return match reader.peek() {
+            Some(c) => Err(ParseBoardError::UnknownSymbol(c)),
+            None => Ok(entity),
+        };
+    }
+}
+
+impl FromStr for Field {
+    type Err = ParseBoardError;
+
+    fn from_str(text: &str) -> Result<Field, ParseBoardError> {
+        let mut fields = fields(text)?;
+        if fields.len() != 1 {
+            return Err(ParseBoardError::FieldCount(fields.len()));
+        }
+        return Ok(fields.remove(0));
+    }
+}
+
+impl FromStr for Lane {
+    type Err = ParseBoardError;
+
+    fn from_str(text: &str) -> Result<Lane, ParseBoardError> {
+        let mut lane = Lane::default();
+        *lane = fields(text)?.try_into().map_err(|fields: Vec<Field>| ParseBoardError::FieldCount(fields.len()))?;
+        return Ok(lane);
+    }
+}
+
+/// blank lines and the indentation of lines are ignored
+impl FromStr for Grid {
+    type Err = ParseBoardError;
+
+    fn from_str(text: &str) -> Result<Grid, ParseBoardError> {
+        let lines: Vec<&str> = text.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+        let mut grid = Grid::default();
+        if lines.len() != grid.len() {
+            return Err(ParseBoardError::LaneCount(lines.len()));
+        }
+
+        for (i, line) in lines.into_iter().enumerate() {
+            let lane = match line {
+                "-" => None,
+                line => Some(line.parse().map_err(|err| ParseBoardError::InLane(i, Box::new(err)))?),
+            };
+            grid.set_lane(i, lane);
+        }
+        return Ok(grid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Behaviour, Bullet, State, Turret, TurretKind, Zombie, DYING_TICKS, LOCAL_PLAYER, ZOMBIE_HEALTH};
+
+    #[test]
+    fn lane_round_trips() {
+        let text = "T(rapid,cd=0)_°(t=4,dmg=3)[T°]_[Z(t=2,blocked)Z]_Z(hp=2,stunned=7)[Z(dying=9)°]";
+        let lane: Lane = text.parse().unwrap();
+
+        assert_eq!(Entity::Turret(Turret { kind: TurretKind::Rapid, tier: 0, owner: LOCAL_PLAYER, cooldown: 0 }), lane[0][0]);
+        assert_eq!(Entity::Bullet(Bullet(4, 3)), lane[2][0]);
+        assert_eq!(2, lane[3].len());
+        assert_eq!(Behaviour::Blocked, match &lane[5][0] {
+            Entity::Zombie(zombie) => zombie.behaviour,
+            _ => unreachable!(),
+        });
+        assert_eq!(text, lane.to_string());
+    }
+
+    #[test]
+    fn default_entities_are_a_single_symbol() {
+        let lane: Lane = "T _ _ _ ° _ _ Z Z".parse().unwrap();
+        assert_eq!(Entity::Turret(Turret::new(TurretKind::Basic, LOCAL_PLAYER)), lane[0][0]);
+        assert_eq!(Entity::Zombie(Zombie::new(0, ZOMBIE_HEALTH)), lane[8][0]);
+        assert_eq!("T___°__ZZ", lane.to_string());
+    }
+
+    #[test]
+    fn grid_round_trips_with_missing_lanes() {
+        let mut state = State::default();
+        for _ in 0..(20 + DYING_TICKS) {
+            state.next();
+        }
+
+        let text = state.grid.to_string();
+        assert_eq!(5, text.lines().count());
+        assert_eq!("-", text.lines().next().unwrap());
+        assert_eq!(state.grid, text.parse().unwrap());
+    }
+
+    #[test]
+    fn grid_ignores_indentation() {
+        let grid: Grid = "
+            -
+            -
+            T_______Z
+            -
+            -
+        "
+        .parse()
+        .unwrap();
+        assert!(grid[0].is_none());
+        assert!(grid[2].as_ref().unwrap()[8].has_zombie());
+        assert_eq!(format!("{:?}", grid), r#"Grid(["-", "-", "T_______Z", "-", "-"])"#);
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        assert_eq!(Err(ParseBoardError::FieldCount(8)), "T_______".parse::<Lane>());
+        assert_eq!(Err(ParseBoardError::UnknownSymbol('X')), "X________".parse::<Lane>());
+        assert_eq!(Err(ParseBoardError::Unclosed('[')), "[ZZ".parse::<Lane>());
+        assert_eq!(Err(ParseBoardError::Unclosed('(')), "Z(hp=1".parse::<Lane>());
+        assert_eq!(Err(ParseBoardError::EmptyStack), "[]________".parse::<Lane>());
+        assert_eq!(Err(ParseBoardError::BadAttribute(String::from("cd"))), "Z(cd=1)".parse::<Entity>());
+        assert_eq!(Err(ParseBoardError::BadAttribute(String::from("dying"))), "Z(blocked,dying=3)".parse::<Entity>());
+        assert_eq!(Err(ParseBoardError::BadValue(String::from("hp=x"))), "Z(hp=x)".parse::<Entity>());
+        assert_eq!(Err(ParseBoardError::Inconsistent(String::from("[TZ]"))), "[TZ]".parse::<Field>());
+        assert_eq!(Err(ParseBoardError::LaneCount(1)), "-".parse::<Grid>());
+        assert_eq!(
+            Err(ParseBoardError::InLane(3, Box::new(ParseBoardError::FieldCount(1)))),
+            "-\n-\n-\nT\n-".parse::<Grid>()
+        );
+    }
+}
@@ -0,0 +1,212 @@
+//! Rules every tick has to keep, checked on random boards and spawn curves
+//! after every `State::next`. Entities have no identity, so movement is
+//! checked by counting: a zombie that moved right would raise the number of
+//! zombies at or right of some field, a bullet that moved left the number of
+//! bullets at or left of some field. Effects are counted the same way, by
+//! kind, place and ticks left.
+#![allow(clippy::needless_return)]
+
+use proptest::prelude::*;
+use zombie_zone_core::{
+    Behaviour, Bullet, Effect, EffectKind, Entity, Field, Grid, Lane, SpawnCurve, State, Turret, TurretKind, Zombie,
+    TURRET_TIERS,
+};
+
+fn zombie() -> impl Strategy<Value = Entity> {
+    let behaviour = prop_oneof![
+        3 => Just(Behaviour::Walking),
+        1 => (0..4u32).prop_map(|until| Behaviour::Stunned { until }),
+        1 => (0..4u32).prop_map(|until| Behaviour::Dying { until }),
+    ];
+    return (1..=5u8, behaviour).prop_map(|(health, behaviour)| {
+        let health = if matches!(behaviour, Behaviour::Dying { .. }) { 0 } else { health };
+        Entity::Zombie(Zombie { last_moved: 0, health, behaviour })
+    });
+}
+
+fn turret() -> impl Strategy<Value = Entity> {
+    let kind = prop_oneof![Just(TurretKind::Basic), Just(TurretKind::Rapid)];
+    return (kind, 0..TURRET_TIERS.len(), 0..2u8, 0..4u32)
+        .prop_map(|(kind, tier, owner, cooldown)| Entity::Turret(Turret { kind, tier, owner, cooldown }));
+}
+
+fn bullet() -> impl Strategy<Value = Entity> {
+    return (1..=3u8).prop_map(|damage| Entity::Bullet(Bullet(0, damage)));
+}
+
+/// only stacks that follow the rules documented on `Field`
+fn field() -> impl Strategy<Value = Field> {
+    let entities = prop_oneof![
+        4 => Just(vec![]),
+        2 => prop::collection::vec(zombie(), 1..=3),
+        1 => prop::collection::vec(bullet(), 1..=2),
+        1 => (turret(), prop::collection::vec(bullet(), 0..=1)).prop_map(|(turret, mut bullets)| {
+            bullets.insert(0, turret);
+            bullets
+        }),
+    ];
+    return entities.prop_map(|entities| Field(entities.into()));
+}
+
+fn grid() -> impl Strategy<Value = Grid> {
+    /* vectors rather than arrays, array strategies of this size overflow the stack of a test thread */
+    let lane = prop::collection::vec(field(), 9).prop_map(|fields| {
+        let mut lane = Lane::default();
+        *lane = fields.try_into().unwrap();
+        lane
+    });
+    return prop::collection::vec(prop::option::weighted(0.7, lane), 5).prop_map(|lanes| {
+        let mut grid = Grid::default();
+        for (i, lane) in lanes.into_iter().enumerate() {
+            grid.set_lane(i, lane);
+        }
+        grid
+    });
+}
+
+fn spawn_curve() -> impl Strategy<Value = SpawnCurve> {
+    return prop_oneof![
+        Just(SpawnCurve::Off),
+        (1..6u32).prop_map(SpawnCurve::Constant),
+        (1..8u32, 1..8u32, 1..30u32).prop_map(|(from, to, over_ticks)| SpawnCurve::LinearRamp { from, to, over_ticks }),
+        (1..8u32, 1..8u32, 0..30u32).prop_map(|(before, after, at)| SpawnCurve::Step { before, after, at }),
+        (1..4u32, 4..10u32, 1..40u32).prop_map(|(min, max, period)| SpawnCurve::Sine { min, max, period }),
+    ];
+}
+
+fn effect() -> impl Strategy<Value = Effect> {
+    let kind = prop_oneof![
+        Just(EffectKind::Explosion),
+        Just(EffectKind::DeathPuff),
+        Just(EffectKind::MuzzleFlash),
+        Just(EffectKind::HitSpark),
+    ];
+    return (kind, 0..5usize, 0..9usize).prop_flat_map(|(kind, lane, field)| {
+        (1..=kind.lifetime()).prop_map(move |ticks_left| Effect { ticks_left, ..Effect::new(kind, lane, field) })
+    });
+}
+
+fn is_zombie(entity: &Entity) -> bool {
+    return matches!(entity, Entity::Zombie(_));
+}
+
+fn is_bullet(entity: &Entity) -> bool {
+    return matches!(entity, Entity::Bullet(_));
+}
+
+/// how many entities `count` of each field of a lane, all 0 for a missing lane
+fn per_field(state: &State, lane: usize, count: impl Fn(usize, &Entity) -> bool) -> [usize; 9] {
+    let mut counts = [0; 9];
+    if let Some(lane) = &state.grid[lane] {
+        for (i, field) in lane.iter().enumerate() {
+            counts[i] = field.iter().filter(|entity| count(i, entity)).count();
+        }
+    }
+    return counts;
+}
+
+fn muzzle_flashes(state: &State, lane: usize) -> [usize; 9] {
+    let mut counts = [0; 9];
+    for effect in state.new_effects() {
+        if effect.lane == lane && effect.kind == EffectKind::MuzzleFlash {
+            counts[effect.field] += 1;
+        }
+    }
+    return counts;
+}
+
+fn check_tick(before: &State, after: &State) -> Result<(), TestCaseError> {
+    prop_assert_eq!(before.tick + 1, after.tick);
+
+    for (i, lane) in after.grid.iter().enumerate() {
+        prop_assert_eq!(before.grid[i].is_some(), lane.is_some());
+        let Some(lane) = lane else { continue };
+
+        /* a zombie that was hit and a bullet in the same field have to be resolved within the tick */
+        for field in lane.iter() {
+            prop_assert!(field.is_consistent(), "lane {}: field {} breaks the stacking rules", i, field);
+        }
+
+        /* turrets stay where they are and only get trampled */
+        for (i_field, field) in lane.iter().enumerate() {
+            for entity in field.iter() {
+                if let Entity::Turret(turret) = entity {
+                    let stayed = before.grid[i].as_ref().unwrap()[i_field].iter().any(|entity| match entity {
+                        Entity::Turret(old) => (old.kind, old.tier, old.owner) == (turret.kind, turret.tier, turret.owner),
+                        _ => false,
+                    });
+                    prop_assert!(stayed, "lane {}: turret {} appeared on field {}", i, entity, i_field);
+                }
+            }
+        }
+
+        /* the only new zombie is the one a lane may spawn on its last field */
+        let zombies_before = per_field(before, i, |_, entity| is_zombie(entity));
+        let zombies_after = per_field(after, i, |_, entity| is_zombie(entity));
+        let spawned = per_field(after, i, |i_field, entity| match entity {
+            Entity::Zombie(zombie) => i_field == 8 && zombie.last_moved == after.tick,
+            _ => false,
+        });
+        prop_assert!(spawned[8] <= 1);
+        for i_field in 0..9 {
+            let before: usize = zombies_before[i_field..].iter().sum();
+            let after: usize = zombies_after[i_field..].iter().sum::<usize>() - spawned[8];
+            prop_assert!(after <= before, "lane {}: a zombie moved right or got duplicated past field {}", i, i_field);
+        }
+
+        /* the only new bullets are the ones turrets fired */
+        let bullets_before = per_field(before, i, |_, entity| is_bullet(entity));
+        let bullets_after = per_field(after, i, |_, entity| is_bullet(entity));
+        let fired = muzzle_flashes(after, i);
+        for i_field in 0..9 {
+            let before: usize = bullets_before[..=i_field].iter().sum::<usize>() + fired[..=i_field].iter().sum::<usize>();
+            let after: usize = bullets_after[..=i_field].iter().sum();
+            prop_assert!(after <= before, "lane {}: a bullet moved left or got duplicated before field {}", i, i_field);
+        }
+    }
+
+    /* effects age by one tick each tick and are gone once their lifetime ran out */
+    let same_place = |a: &Effect, b: &Effect| (a.kind, a.lane, a.field) == (b.kind, b.lane, b.field);
+    for effect in &after.effects {
+        prop_assert!(
+            0 < effect.ticks_left && effect.ticks_left <= effect.kind.lifetime(),
+            "{:?} outlived its lifetime of {}",
+            effect,
+            effect.kind.lifetime()
+        );
+        if effect.ticks_left == effect.kind.lifetime() {
+            continue;
+        }
+        let aged = after.effects.iter().filter(|other| same_place(effect, other) && other.ticks_left == effect.ticks_left).count();
+        let younger = before
+            .effects
+            .iter()
+            .filter(|other| same_place(effect, other) && other.ticks_left == effect.ticks_left + 1)
+            .count();
+        prop_assert!(aged <= younger, "{:?} did not age by exactly one tick", effect);
+    }
+    return Ok(());
+}
+
+proptest! {
+    #[test]
+    fn every_tick_keeps_the_invariants(
+        grid in grid(),
+        effects in prop::collection::vec(effect(), 0..4),
+        curves in prop::collection::vec(spawn_curve(), 5),
+        ticks in 1..40u32,
+    ) {
+        let mut state = State::default();
+        state.grid = grid;
+        state.effects = effects;
+        for (lane, curve) in curves.into_iter().enumerate() {
+            state.set_spawn_curve(lane, curve);
+        }
+
+        for _ in 0..ticks {
+            let before = state.clone();
+            state.next();
+            check_tick(&before, &state)?;
+        }
+    }
+}
@@ -0,0 +1,39 @@
+//! Plays every `tests/scenarios/*.scenario` file. `BLESS=1 cargo test`
+//! rewrites the expected boards of the files with what they play out to.
+#![allow(clippy::needless_return)]
+
+use std::{env, fs, path::PathBuf};
+
+use zombie_zone_core::Scenario;
+
+fn scenario_files() -> Vec<PathBuf> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/scenarios");
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "scenario"))
+        .collect();
+    paths.sort();
+    return paths;
+}
+
+#[test]
+fn scenarios() {
+    let bless = env::var_os("BLESS").is_some();
+    let paths = scenario_files();
+    assert!(!paths.is_empty());
+
+    let mut failures = String::new();
+    for path in paths {
+        let text = fs::read_to_string(&path).unwrap();
+        let mut scenario = Scenario::parse(&text).unwrap_or_else(|err| panic!("{}: {:?}", path.display(), err));
+
+        if bless {
+            scenario.bless();
+            fs::write(&path, scenario.to_string()).unwrap();
+        } else if let Err(mismatch) = scenario.run() {
+            failures += &format!("{}: {}", path.display(), mismatch.diff());
+        }
+    }
+    assert!(failures.is_empty(), "scenarios played out differently:\n{}", failures);
+}
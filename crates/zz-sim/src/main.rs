@@ -0,0 +1,200 @@
+//! Runs the zombie zone simulation in the terminal, for trying out rule
+//! changes, balance sweeps and reproducing recorded games.
+//!
+//! ```text
+//! zz-sim --ticks 200 --format stats
+//! zz-sim --level level.json --script horde.rhai --format jsonl
+//! zz-sim --replay bug-report.json --format ascii
+//! ```
+#![allow(clippy::needless_return)]
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use clap::{Parser, ValueEnum};
+use zombie_zone_core::{LevelScript, Replay, SavedState, ScriptError, ScriptLimits, State, Tick};
+
+mod output;
+
+#[derive(Parser)]
+#[command(name = "zz-sim", about = "Runs the zombie zone simulation without a browser")]
+struct Args {
+    /// start from a save, the level the game opens with otherwise
+    #[arg(long, conflicts_with = "replay")]
+    level: Option<PathBuf>,
+    /// play a recorded game, fails with the first tick it diverges from its recording at
+    #[arg(long)]
+    replay: Option<PathBuf>,
+    /// a Rhai level script run after every tick, a replay plays without its level script
+    #[arg(long, conflicts_with = "replay")]
+    script: Option<PathBuf>,
+    /// how many ticks to run, defaults to the length of the replay or 100
+    #[arg(long)]
+    ticks: Option<Tick>,
+    #[arg(long, value_enum, default_value_t = Format::Stats)]
+    format: Format,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// the grid after every tick
+    Ascii,
+    /// statistics once the run is over
+    Stats,
+    /// one JSON object per tick
+    Jsonl,
+}
+
+enum RunError {
+    /// whoever read the output went away, as `zz-sim | head` does
+    Closed,
+    Failed(String),
+}
+
+impl From<String> for RunError {
+    fn from(message: String) -> RunError {
+        return RunError::Failed(message);
+    }
+}
+
+impl From<io::Error> for RunError {
+    fn from(err: io::Error) -> RunError {
+        if err.kind() == io::ErrorKind::BrokenPipe {
+            return RunError::Closed;
+        }
+        return RunError::Failed(format!("writing the output failed: {}", err));
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) | Err(RunError::Closed) => return ExitCode::SUCCESS,
+        Err(RunError::Failed(err)) => {
+            eprintln!("zz-sim: {}", err);
+            return ExitCode::FAILURE;
+        }
+    }
+}
+
+fn run(args: &Args) -> Result<(), RunError> {
+    let replay = match &args.replay {
+        Some(path) => Some(Replay::from_json(&read(path)?).map_err(|err| format!("{}: {:?}", path.display(), err))?),
+        None => None,
+    };
+    let mut state = match (&args.level, &replay) {
+        (Some(path), _) => load_level(path)?,
+        (None, Some(replay)) => replay.start.clone(),
+        (None, None) => State::default(),
+    };
+    let mut script = match &args.script {
+        Some(path) => Some(
+            LevelScript::new(&read(path)?, ScriptLimits::default())
+                .map_err(|err| format!("{}: {:?}", path.display(), err))?,
+        ),
+        None => None,
+    };
+    let ticks = args.ticks.unwrap_or(match &replay {
+        Some(replay) => replay.ticks,
+        None => 100,
+    });
+
+    /* println! would panic once stdout is closed */
+    let mut out = io::stdout().lock();
+    let mut stats = output::Stats::default();
+    /* the first tick the replay played differently, later ticks differ as a consequence */
+    let mut diverged = None;
+    if args.format == Format::Ascii {
+        writeln!(out, "{}", output::frame(&state))?;
+    }
+
+    for _ in 0..ticks {
+        match (&replay, &mut script) {
+            (Some(replay), _) if state.tick < replay.tick() => {
+                replay.step(&mut state);
+                if diverged.is_none() && !matches_recording(replay, &state) {
+                    diverged = Some(state.tick);
+                }
+            }
+            (_, Some(script)) => match script.next(&mut state) {
+                Ok(()) => {}
+                /* the rest of the tick went through, keep going */
+                Err(ScriptError::Rejected(command, err)) => {
+                    eprintln!("tick {}: script command {:?} rejected: {:?}", state.tick, command, err);
+                }
+                Err(err) => return Err(format!("tick {}: script failed: {:?}", state.tick, err).into()),
+            },
+            _ => state.next(),
+        }
+
+        stats.record(&state);
+        match args.format {
+            Format::Ascii => writeln!(out, "{}", output::frame(&state))?,
+            Format::Jsonl => writeln!(out, "{}", output::json_line(&state))?,
+            Format::Stats => {}
+        }
+    }
+
+    if args.format == Format::Stats {
+        write!(out, "{}", stats.report(&state))?;
+    }
+    out.flush()?;
+    if let Some(tick) = diverged {
+        return Err(format!("replay diverged from its recording at tick {}", tick).into());
+    }
+    return Ok(());
+}
+
+fn read(path: &PathBuf) -> Result<String, String> {
+    return fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err));
+}
+
+/// a save as written by the game, older schema versions get migrated
+fn load_level(path: &PathBuf) -> Result<State, String> {
+    let value = serde_json::from_str(&read(path)?).map_err(|err| format!("{}: {}", path.display(), err))?;
+    let saved = SavedState::from_value(value).map_err(|err| format!("{}: {:?}", path.display(), err))?;
+    return Ok(saved.state);
+}
+
+/// false if the replay recorded a different hash for the tick `state` is at
+fn matches_recording(replay: &Replay, state: &State) -> bool {
+    let played = (state.tick - replay.start.tick) as usize;
+    return match replay.hashes.get(played - 1) {
+        Some(hash) => *hash == state.hash(),
+        None => true,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diverging_replay_fails_with_its_first_tick() {
+        let mut replay = Replay::new(State::default());
+        let mut state = replay.start.clone();
+        for _ in 0..5 {
+            state.next();
+            replay.record_next(&state);
+        }
+        replay.hashes[1] ^= 1;
+        let path = std::env::temp_dir().join(format!("zz-sim-diverged-{}.json", std::process::id()));
+        fs::write(&path, replay.to_json()).unwrap();
+
+        let args = Args::try_parse_from(["zz-sim", "--replay", path.to_str().unwrap(), "--format", "stats"]).unwrap();
+        let result = run(&args);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(RunError::Failed(message)) if message == "replay diverged from its recording at tick 2"));
+    }
+
+    #[test]
+    fn replay_takes_no_level_or_script() {
+        assert!(Args::try_parse_from(["zz-sim", "--replay", "game.json", "--script", "horde.rhai"]).is_err());
+        assert!(Args::try_parse_from(["zz-sim", "--replay", "game.json", "--level", "level.json"]).is_err());
+        assert!(Args::try_parse_from(["zz-sim", "--level", "level.json", "--script", "horde.rhai"]).is_ok());
+    }
+}
@@ -0,0 +1,144 @@
+use zombie_zone_core::{EffectKind, Field, Role, State, Tick};
+
+/// a field is drawn this many characters wide
+const FIELD_WIDTH: usize = 3;
+
+/// The grid as text, one line per lane. A field shows the glyphs of its
+/// stack, a stack too high for the field shows its first glyph and a count.
+pub fn frame(state: &State) -> String {
+    let mut frame = format!("tick {}  money {}\n", state.tick, state.money);
+
+    for (i, lane) in state.grid.iter().enumerate() {
+        let fields = match lane {
+            Some(lane) => lane.iter().map(field).collect::<Vec<_>>().join(" "),
+            None => String::from("-"),
+        };
+        frame += &format!("{} | {}\n", i + 1, fields);
+    }
+    return frame;
+}
+
+fn field(field: &Field) -> String {
+    let glyphs: Vec<&str> = field.iter().map(|entity| entity.behavior().glyph().symbol).collect();
+    let text = match glyphs.len() {
+        0 => String::from("_"),
+        len if len <= FIELD_WIDTH => glyphs.concat(),
+        len => format!("{}{}", glyphs[0], len),
+    };
+    return format!("{:<width$}", text, width = FIELD_WIDTH);
+}
+
+/// one JSON object per tick, the grid in the shape of a save
+pub fn json_line(state: &State) -> String {
+    let line = serde_json::json!({
+        "tick": state.tick,
+        "money": state.money,
+        "hash": format!("{:016x}", state.hash()),
+        "effects": state.new_effects().collect::<Vec<_>>(),
+        "grid": state.grid,
+    });
+    return line.to_string();
+}
+
+/// counted over the ticks the simulation ran
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub ticks: Tick,
+    pub kills: u32,
+    pub shots: u32,
+    pub turrets_lost: u32,
+}
+
+impl Stats {
+    /// counts what the tick that led to `state` did
+    pub fn record(&mut self, state: &State) {
+        self.ticks += 1;
+        for effect in state.new_effects() {
+            match effect.kind {
+                EffectKind::DeathPuff => self.kills += 1,
+                EffectKind::MuzzleFlash => self.shots += 1,
+                EffectKind::Explosion => self.turrets_lost += 1,
+                EffectKind::HitSpark => {}
+            }
+        }
+    }
+
+    pub fn report(&self, state: &State) -> String {
+        let count = |role: Role| {
+            state
+                .grid
+                .iter()
+                .flatten()
+                .flat_map(|lane| lane.iter())
+                .flat_map(|field| field.iter())
+                .filter(|entity| entity.behavior().role() == role && (role != Role::Attacker || entity.behavior().is_target()))
+                .count()
+        };
+
+        let mut report = String::new();
+        report += &format!("ticks         {}\n", self.ticks);
+        report += &format!("final tick    {}\n", state.tick);
+        report += &format!("money         {}\n", state.money);
+        report += &format!("kills         {}\n", self.kills);
+        report += &format!("shots         {}\n", self.shots);
+        report += &format!("turrets lost  {}\n", self.turrets_lost);
+        report += &format!("zombies left  {}\n", count(Role::Attacker));
+        report += &format!("turrets left  {}\n", count(Role::Defender));
+        report += &format!("hash          {:016x}\n", state.hash());
+        return report;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zombie_zone_core::{Entity, Zombie, ZOMBIE_HEALTH};
+
+    #[test]
+    fn frame_draws_every_lane() {
+        /* a turret on the first field and a zombie on each of the last two */
+        let mut state = State::default();
+        for _ in 0..3 {
+            state.place_entity(2, 8, Entity::Zombie(Zombie::new(0, ZOMBIE_HEALTH))).unwrap();
+        }
+
+        let expected = [
+            "tick 0  money 100",
+            "1 | -",
+            "2 | -",
+            "3 | T   _   _   _   _   _   _   Z   Z4 ",
+            "4 | -",
+            "5 | -",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(expected, frame(&state));
+    }
+
+    #[test]
+    fn stats_count_effects_of_each_tick() {
+        let mut state = State::default();
+        let mut stats = Stats::default();
+        for _ in 0..40 {
+            state.next();
+            stats.record(&state);
+        }
+
+        assert_eq!(40, stats.ticks);
+        assert!(stats.shots > 0);
+        assert!(stats.kills > 0);
+        assert!(stats.report(&state).contains(&format!("kills         {}\n", stats.kills)));
+    }
+
+    #[test]
+    fn json_line_is_one_line() {
+        let mut state = State::new();
+        state.next();
+
+        let line = json_line(&state);
+        assert!(!line.contains('\n'));
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(1, value["tick"]);
+        assert_eq!(5, value["grid"].as_array().unwrap().len());
+    }
+}
@@ -0,0 +1,199 @@
+use std::time::Instant;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use zombie_zone_core::{Action, Clock, Scheduler, State, TurretKind, LOCAL_PLAYER};
+
+/// the monotonic clock of the process
+pub struct InstantClock(Instant);
+
+impl Clock for InstantClock {
+    fn now_ms(&self) -> f64 {
+        return self.0.elapsed().as_secs_f64() * 1000.0;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Step,
+    ToggleRun,
+    /// lanes and fields to move the cursor by
+    Move(isize, isize),
+    PlaceTurret(TurretKind),
+    UpgradeTurret,
+    SellTurret,
+    Reset,
+    Quit,
+}
+
+impl Command {
+    pub fn from_key(key: KeyEvent) -> Option<Command> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Some(Command::Quit);
+        }
+
+        return match key.code {
+            KeyCode::Char(' ') | KeyCode::Char('n') => Some(Command::Step),
+            KeyCode::Enter | KeyCode::Char('a') => Some(Command::ToggleRun),
+            KeyCode::Up | KeyCode::Char('k') => Some(Command::Move(-1, 0)),
+            KeyCode::Down | KeyCode::Char('j') => Some(Command::Move(1, 0)),
+            KeyCode::Left | KeyCode::Char('h') => Some(Command::Move(0, -1)),
+            KeyCode::Right | KeyCode::Char('l') => Some(Command::Move(0, 1)),
+            KeyCode::Char('t') => Some(Command::PlaceTurret(TurretKind::Basic)),
+            KeyCode::Char('y') => Some(Command::PlaceTurret(TurretKind::Rapid)),
+            KeyCode::Char('u') => Some(Command::UpgradeTurret),
+            KeyCode::Char('s') => Some(Command::SellTurret),
+            KeyCode::Char('r') => Some(Command::Reset),
+            KeyCode::Char('q') | KeyCode::Esc => Some(Command::Quit),
+            _ => None,
+        };
+    }
+}
+
+pub struct App {
+    pub state: State,
+    /// (lane, field) the turret keys act on, always on an existing lane
+    pub cursor: (usize, usize),
+    /// set while the game runs on its own
+    pub scheduler: Option<Scheduler<InstantClock>>,
+    /// the outcome of the last command
+    pub message: String,
+    pub quit: bool,
+}
+
+impl App {
+    pub fn new(state: State) -> App {
+        let mut app = App {
+            state,
+            cursor: (0, 0),
+            scheduler: None,
+            message: String::new(),
+            quit: false,
+        };
+        let first_lane = app.lanes().next().unwrap_or(0);
+        app.cursor = (first_lane, 0);
+        return app;
+    }
+
+    fn lanes(&self) -> impl DoubleEndedIterator<Item = usize> + '_ {
+        self.state.grid.iter().enumerate().filter(|(_, lane)| lane.is_some()).map(|(i, _)| i)
+    }
+
+    pub fn handle(&mut self, command: Command) {
+        let (lane, field) = self.cursor;
+        let action = match command {
+            Command::Step => {
+                self.state.next();
+                None
+            }
+            Command::ToggleRun => {
+                self.scheduler = match self.scheduler {
+                    Some(_) => None,
+                    None => Some(Scheduler::new(InstantClock(Instant::now()), self.state.tick_interval_ms())),
+                };
+                None
+            }
+            Command::Move(lanes, fields) => {
+                self.move_cursor(lanes, fields);
+                None
+            }
+            Command::PlaceTurret(kind) => Some(Action::PlaceTurret { player: LOCAL_PLAYER, lane, field, kind }),
+            Command::UpgradeTurret => Some(Action::UpgradeTurret { player: LOCAL_PLAYER, lane, field }),
+            Command::SellTurret => Some(Action::SellTurret { player: LOCAL_PLAYER, lane, field }),
+            Command::Reset => {
+                /* same as the reset button of the web game */
+                *self = App::new(State::new());
+                None
+            }
+            Command::Quit => {
+                self.quit = true;
+                None
+            }
+        };
+
+        if let Some(action) = action {
+            self.message = match self.state.apply(&action) {
+                Ok(_) => String::new(),
+                Err(err) => format!("{:?}", err),
+            };
+        }
+    }
+
+    fn move_cursor(&mut self, lanes: isize, fields: isize) {
+        let (lane, field) = self.cursor;
+        let next_lane = match lanes {
+            0 => Some(lane),
+            lanes if lanes < 0 => self.lanes().rev().find(|&i| i < lane),
+            _ => self.lanes().find(|&i| i > lane),
+        };
+        let fields_in_lane = self.state.grid[lane].as_ref().map_or(1, |lane| lane.len());
+        let next_field = field.saturating_add_signed(fields).min(fields_in_lane - 1);
+
+        self.cursor = (next_lane.unwrap_or(lane), next_field);
+    }
+
+    /// runs the ticks that are due while running, call it once per drawn frame
+    pub fn frame(&mut self) {
+        if let Some(scheduler) = &mut self.scheduler {
+            for _ in 0..scheduler.frame() {
+                self.state.next();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zombie_zone_core::{ActionError, TURRET_TIERS};
+
+    fn press(app: &mut App, code: KeyCode) {
+        let command = Command::from_key(KeyEvent::new(code, KeyModifiers::NONE)).unwrap();
+        app.handle(command);
+    }
+
+    #[test]
+    fn keys_place_upgrade_and_sell_a_turret() {
+        let mut app = App::new(State::new());
+        assert_eq!((2, 0), app.cursor);
+
+        press(&mut app, KeyCode::Right);
+        press(&mut app, KeyCode::Char('t'));
+        assert!(app.state.turret_at(2, 1).is_some());
+        assert_eq!(100 - TURRET_TIERS[0].cost, app.state.money);
+
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(Some(1), app.state.turret_at(2, 1).map(|turret| turret.tier));
+
+        press(&mut app, KeyCode::Char('s'));
+        assert!(app.state.turret_at(2, 1).is_none());
+
+        press(&mut app, KeyCode::Char('s'));
+        assert_eq!(format!("{:?}", ActionError::NoTurret), app.message);
+    }
+
+    #[test]
+    fn cursor_stays_on_existing_lanes_and_fields() {
+        let mut app = App::new(State::new());
+        press(&mut app, KeyCode::Up);
+        press(&mut app, KeyCode::Left);
+        assert_eq!((2, 0), app.cursor);
+
+        for _ in 0..20 {
+            press(&mut app, KeyCode::Char('l'));
+        }
+        assert_eq!((2, 8), app.cursor);
+    }
+
+    #[test]
+    fn step_and_reset() {
+        let mut app = App::new(State::default());
+        press(&mut app, KeyCode::Char(' '));
+        press(&mut app, KeyCode::Char('a'));
+        assert_eq!(1, app.state.tick);
+        assert!(app.scheduler.is_some());
+
+        press(&mut app, KeyCode::Char('r'));
+        assert!(app.state == State::new());
+        assert!(app.scheduler.is_none());
+    }
+}
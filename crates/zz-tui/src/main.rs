@@ -0,0 +1,45 @@
+//! The zombie zone in the terminal, playing the same `State` as the web game.
+#![allow(clippy::needless_return)]
+
+use std::{io, time::Duration};
+
+use crossterm::event::{self, Event, KeyEventKind};
+use zombie_zone_core::State;
+
+mod app;
+mod ui;
+
+use app::{App, Command};
+
+/// how long to wait for a key before drawing the next frame
+const FRAME: Duration = Duration::from_millis(16);
+
+fn main() -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal);
+    ratatui::restore();
+    return result;
+}
+
+fn run(terminal: &mut ratatui::DefaultTerminal) -> io::Result<()> {
+    /* the level the web game opens with */
+    let mut app = App::new(State::default());
+
+    while !app.quit {
+        app.frame();
+        terminal.draw(|frame| ui::draw(frame, &app))?;
+
+        if !event::poll(FRAME)? {
+            continue;
+        }
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                if let Some(command) = Command::from_key(key) {
+                    app.handle(command);
+                }
+            }
+            _ => {}
+        }
+    }
+    return Ok(());
+}
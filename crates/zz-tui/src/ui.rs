@@ -0,0 +1,148 @@
+use ratatui::{
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+use zombie_zone_core::{Field, GlyphStyle, State};
+
+use crate::app::App;
+
+/// a field is drawn this many columns wide, whatever it holds
+const FIELD_WIDTH: usize = 3;
+
+const HELP: &str =
+    "space step  enter run/stop  arrows move  t turret  y rapid turret  u upgrade  s sell  r reset  q quit";
+
+/// the terminal colours of a glyph style, like the css classes of the web `Grid`
+fn glyph_style(style: GlyphStyle) -> Style {
+    return match style {
+        GlyphStyle::Plain => Style::new(),
+        GlyphStyle::Alert => Style::new().fg(Color::Red),
+        GlyphStyle::Muted => Style::new().fg(Color::Gray),
+        GlyphStyle::Cold => Style::new().fg(Color::LightBlue),
+        GlyphStyle::Faded => Style::new().add_modifier(Modifier::DIM),
+    };
+}
+
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [header, grid, message, help] = Layout::vertical([
+        Constraint::Length(2),
+        Constraint::Length(app.state.grid.len() as u16),
+        Constraint::Length(2),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let running = if app.scheduler.is_some() { "running" } else { "paused" };
+    let status = format!("Tick: {}  Money: {}  {}", app.state.tick, app.state.money, running);
+    frame.render_widget(Paragraph::new(status).bold(), header);
+
+    let lanes: Vec<Line> = (0..app.state.grid.len()).map(|i| lane(&app.state, i, app.cursor)).collect();
+    frame.render_widget(Paragraph::new(lanes), grid);
+
+    frame.render_widget(Paragraph::new(app.message.as_str()).red(), message);
+    frame.render_widget(Paragraph::new(HELP).dark_gray(), help);
+}
+
+/// one lane in the layout of the web `Grid`, `Lane 3: | T   _   Z2  |`
+fn lane(state: &State, i: usize, cursor: (usize, usize)) -> Line<'static> {
+    let mut spans = vec![Span::raw(format!("Lane {}: |", i + 1))];
+
+    if let Some(lane) = &state.grid[i] {
+        for (j, field) in lane.iter().enumerate() {
+            spans.push(Span::raw(" "));
+
+            let cell = match state.effects_at(i, j).last() {
+                /* an effect covers whatever the field holds until it fades */
+                Some(effect) => vec![Span::styled(effect.kind.glyph(), Style::new().fg(Color::Yellow))],
+                None => stack(field),
+            };
+            let mut cell = fit(cell, FIELD_WIDTH);
+            if cursor == (i, j) {
+                cell = cell.into_iter().map(|span| span.add_modifier(Modifier::REVERSED)).collect();
+            }
+            spans.extend(cell);
+        }
+    }
+
+    spans.push(Span::raw(" |"));
+    return Line::from(spans);
+}
+
+/// cuts or pads the spans of a field to `width` columns, so the fields of a lane never shift
+fn fit(spans: Vec<Span<'static>>, width: usize) -> Vec<Span<'static>> {
+    let mut fitted = vec![];
+    let mut left = width;
+    for span in spans {
+        let text: String = span.content.chars().take(left).collect();
+        left -= text.chars().count();
+        fitted.push(Span::styled(text, span.style));
+    }
+    if left > 0 {
+        fitted.push(Span::raw(" ".repeat(left)));
+    }
+    return fitted;
+}
+
+/// each kind of entity of the stack once, followed by how often it is on the field
+fn stack(field: &Field) -> Vec<Span<'static>> {
+    if field.is_empty() {
+        return vec![Span::raw("_")];
+    }
+
+    let mut glyphs: Vec<(&'static str, GlyphStyle, usize)> = vec![];
+    for entity in field.iter() {
+        let glyph = entity.behavior().glyph();
+        match glyphs.iter_mut().find(|(symbol, style, _)| *symbol == glyph.symbol && *style == glyph.style) {
+            Some((_, _, count)) => *count += 1,
+            None => glyphs.push((glyph.symbol, glyph.style, 1)),
+        }
+    }
+
+    return glyphs
+        .into_iter()
+        .map(|(symbol, style, count)| {
+            let text = if count > 1 { format!("{}{}", symbol, count) } else { symbol.to_string() };
+            Span::styled(text, glyph_style(style))
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+    use zombie_zone_core::{Effect, EffectKind, Entity, Zombie, ZOMBIE_HEALTH};
+
+    #[test]
+    fn draws_lanes_with_web_glyphs() {
+        let mut terminal = Terminal::new(TestBackend::new(100, 10)).unwrap();
+        let app = App::new(State::default());
+
+        terminal.draw(|frame| draw(frame, &app)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let row = |y: u16| (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect::<String>();
+        assert_eq!("Tick: 0  Money: 100  paused", row(0).trim_end());
+        assert_eq!("Lane 1: | |", row(2).trim_end());
+        assert_eq!("Lane 3: | T   _   _   _   _   _   _   Z   Z   |", row(4).trim_end());
+    }
+
+    #[test]
+    fn effects_and_stacks_keep_the_field_width() {
+        let mut terminal = Terminal::new(TestBackend::new(100, 10)).unwrap();
+        let mut app = App::new(State::default());
+        app.state.effects.push(Effect::new(EffectKind::Explosion, 2, 1));
+        for _ in 0..2 {
+            app.state.place_entity(2, 8, Entity::Zombie(Zombie::new(0, ZOMBIE_HEALTH))).unwrap();
+        }
+
+        terminal.draw(|frame| draw(frame, &app)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let row = (0..buffer.area.width).map(|x| buffer[(x, 4)].symbol()).collect::<String>();
+        assert_eq!("Lane 3: | T   *   _   _   _   _   _   Z   Z3  |", row.trim_end());
+    }
+}
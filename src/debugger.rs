@@ -0,0 +1,112 @@
+use std::rc::Rc;
+
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::{game, glyph_class, store::GameStore};
+
+/// what the scrubbed state was replayed from: the selected tick, the start of
+/// the recording, its length and its last hash
+type ScrubKey = (game::Tick, game::Tick, game::Tick, usize, Option<u64>);
+/// the state after the selected tick and the fields that tick changed
+type Scrubbed = Rc<(game::State, Vec<(usize, usize)>)>;
+
+/// Steps through the recording of the session. Shows the grid at the chosen
+/// tick and marks every field that changed during that tick.
+#[function_component(Debugger)]
+pub fn debugger() -> Html {
+    let (store, _) = use_store::<GameStore>();
+    let open = use_state(|| false);
+    /* ticks since the start of the recording, None follows the latest tick */
+    let selected = use_state(|| None::<game::Tick>);
+    /* replaying the recording is O(session length), it only runs again once the tick or the recording changed */
+    let scrubbed = use_mut_ref(|| None::<(ScrubKey, Scrubbed)>);
+
+    let toggle = {
+        let open = open.clone();
+        Callback::from(move |_: MouseEvent| open.set(!*open))
+    };
+    if !*open {
+        return html! {
+            <button onclick={toggle} type="button" class="py-1 px-2 rounded-md border-2 border-gray-200">{"Debugger"}</button>
+        };
+    }
+
+    let recording = store.recording.borrow();
+    let ticks = selected.unwrap_or(recording.ticks).min(recording.ticks);
+    let scrub = {
+        let selected = selected.clone();
+        let latest = recording.ticks;
+        Callback::from(move |e: InputEvent| {
+            let ticks = e.target_unchecked_into::<HtmlInputElement>().value().parse().unwrap_or(latest);
+            selected.set(if ticks >= latest { None } else { Some(ticks) });
+        })
+    };
+
+    let key = (ticks, recording.start.tick, recording.ticks, recording.actions.len(), recording.hashes.last().copied());
+    let cached = match &*scrubbed.borrow() {
+        Some((cached_key, cached)) if *cached_key == key => Some(cached.clone()),
+        _ => None,
+    };
+    let shown = match cached {
+        Some(cached) => cached,
+        None => {
+            /* the state one tick earlier, to see what the selected tick changed */
+            let mut state = recording.state_after(ticks.saturating_sub(1));
+            let before = state.grid.clone();
+            if ticks > 0 {
+                recording.step(&mut state);
+            }
+            let changed = before.changed_fields(&state.grid);
+            let result = Rc::new((state, changed));
+            *scrubbed.borrow_mut() = Some((key, result.clone()));
+            result
+        }
+    };
+    let (state, changed) = &*shown;
+
+    let mut lanes = vec![];
+    for (i, lane) in state.grid.iter().enumerate() {
+        let lane = match lane {
+            Some(lane) => lane,
+            None => continue,
+        };
+
+        let fields = lane
+            .iter()
+            .enumerate()
+            .map(|(j, field)| {
+                let class = if changed.contains(&(i, j)) { "w-12 bg-yellow-900" } else { "w-12" };
+                let stack = field
+                    .iter()
+                    .map(|entity| {
+                        let glyph = entity.behavior().glyph();
+                        let class = glyph_class(glyph.style);
+                        html! { <span {class} title={format!("{:?}", entity)}>{glyph.symbol}</span> }
+                    })
+                    .collect::<Html>();
+                html! { <div {class}>{"["}{stack}{"]"}</div> }
+            })
+            .collect::<Html>();
+
+        lanes.push(html! {
+            <div class="flex flex-row gap-1">
+                <div>{"Lane "}{i + 1}{":"}</div>
+                {fields}
+            </div>
+        });
+    }
+
+    html! {
+        <div class="flex flex-col gap-2 p-2 border-2 border-gray-700 font-mono">
+            <div class="flex flex-row gap-2 items-center">
+                <button onclick={toggle} type="button" class="py-1 px-2 rounded-md border-2 border-gray-200">{"Close debugger"}</button>
+                <input type="range" min="0" max={recording.ticks.to_string()} value={ticks.to_string()} oninput={scrub} class="w-96" />
+                <div>{"tick "}{state.tick}{" ("}{ticks}{" of "}{recording.ticks}{" recorded), hash "}{format!("{:016x}", state.hash())}</div>
+            </div>
+            <div>{changed.len()}{" fields changed during this tick"}</div>
+            {lanes.into_iter().collect::<Html>()}
+        </div>
+    }
+}
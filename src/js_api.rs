@@ -0,0 +1,185 @@
+//! The game for scripts on the page (analytics widgets, the level editor),
+//! which cannot reach the yewdux store. States, grids and actions cross as
+//! plain JS objects in the JSON shape documented on `game::SavedState`.
+//!
+//! ```js
+//! const game = Game.fromLevel(levelJson);
+//! game.onTick((event) => console.log(event.tick, event.money));
+//! game.step(10);
+//! game.apply({ SellTurret: { player: 0, lane: 2, field: 0 } });
+//! ```
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use wasm_bindgen::prelude::*;
+use yewdux::prelude::*;
+
+use crate::{game, store::GameStore};
+
+/// A simulation of its own, nothing done to it reaches the game on the page.
+/// Every method takes `&self` and borrows the fields only for as long as it
+/// needs them, so tick listeners can call back into the game; wasm-bindgen
+/// would refuse any call while a `&mut self` method runs.
+#[wasm_bindgen]
+pub struct Game {
+    state: RefCell<game::State>,
+    listeners: RefCell<Vec<(u32, js_sys::Function)>>,
+    next_listener: Cell<u32>,
+}
+
+/// keeps a listener on the game on the page subscribed, until `free()` gets called
+#[wasm_bindgen]
+pub struct GameSubscription {
+    _dispatch: Dispatch<GameStore>,
+}
+
+impl Game {
+    fn from_state(state: game::State) -> Game {
+        Game {
+            state: RefCell::new(state),
+            listeners: RefCell::new(vec![]),
+            next_listener: Cell::new(0),
+        }
+    }
+}
+
+impl Default for Game {
+    fn default() -> Game {
+        Game::from_state(game::State::new())
+    }
+}
+
+#[wasm_bindgen]
+impl Game {
+    /// the default level
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Game {
+        Game::default()
+    }
+
+    /// a level in the JSON shape of a save, older schema versions get migrated
+    #[wasm_bindgen(js_name = fromLevel)]
+    pub fn from_level(json: &str) -> Result<Game, JsError> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        let saved = game::SavedState::from_value(value).map_err(|err| JsError::new(&format!("{:?}", err)))?;
+        return Ok(Game::from_state(saved.state));
+    }
+
+    /// a copy of the game running on the page, as of now
+    pub fn current() -> Game {
+        Game::from_state(Dispatch::<GameStore>::new().get().state.clone())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn tick(&self) -> game::Tick {
+        self.state.borrow().tick
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn money(&self) -> game::Money {
+        self.state.borrow().money
+    }
+
+    /// runs `ticks` ticks and calls every tick listener after each of them,
+    /// stops at the first listener that throws and rethrows its exception
+    pub fn step(&self, ticks: u32) -> Result<(), JsValue> {
+        for _ in 0..ticks {
+            let event = {
+                let mut state = self.state.borrow_mut();
+                state.next();
+                tick_event(&state)
+            };
+
+            /* the tick is done and nothing is borrowed, listeners may call back into the game */
+            let listeners: Vec<js_sys::Function> =
+                self.listeners.borrow().iter().map(|(_, listener)| listener.clone()).collect();
+            for listener in &listeners {
+                listener.call1(&JsValue::NULL, &event)?;
+            }
+        }
+        return Ok(());
+    }
+
+    /// `action` as in `{ UpgradeTurret: { player, lane, field } }`, returns the refund of a sold turret
+    pub fn apply(&self, action: JsValue) -> Result<game::Money, JsError> {
+        let json = js_sys::JSON::stringify(&action).map_err(|_| JsError::new("action is not JSON"))?;
+        let action: game::Action = serde_json::from_str(&String::from(json))?;
+        return self.state.borrow_mut().apply(&action).map_err(|err| JsError::new(&format!("{:?}", err)));
+    }
+
+    /// 5 lanes of 9 fields, a missing lane is `null`
+    pub fn grid(&self) -> JsValue {
+        to_js(serde_json::to_string(&self.state.borrow().grid).unwrap())
+    }
+
+    #[wasm_bindgen(js_name = gridJson)]
+    pub fn grid_json(&self) -> String {
+        serde_json::to_string(&self.state.borrow().grid).unwrap()
+    }
+
+    /// the whole game as a save, `Game.fromLevel` takes it back
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&game::SavedState::new(self.state.borrow().clone())).unwrap()
+    }
+
+    /// `listener` gets called with `{ tick, money, effects }` after every tick,
+    /// returns the id to unsubscribe with. It may use the game it listens to:
+    /// reading, applying actions and (un)subscribing take effect right away,
+    /// a `step` from within a listener runs its ticks and their listeners
+    /// before the outer step goes on. Listeners added or removed during a
+    /// tick are called from the next tick on.
+    #[wasm_bindgen(js_name = onTick)]
+    pub fn on_tick(&self, listener: js_sys::Function) -> u32 {
+        let id = self.next_listener.get();
+        self.next_listener.set(id + 1);
+        self.listeners.borrow_mut().push((id, listener));
+        return id;
+    }
+
+    /// whether there was a listener with that id
+    #[wasm_bindgen(js_name = offTick)]
+    pub fn off_tick(&self, id: u32) -> bool {
+        let mut listeners = self.listeners.borrow_mut();
+        let before = listeners.len();
+        listeners.retain(|(listener_id, _)| *listener_id != id);
+        return listeners.len() != before;
+    }
+}
+
+/// calls `listener` with `{ tick, money, effects }` whenever the tick of the
+/// game on the page changes, that includes undo and loading a save
+#[wasm_bindgen(js_name = onGameTick)]
+pub fn on_game_tick(listener: js_sys::Function) -> GameSubscription {
+    let last_tick = Cell::new(Dispatch::<GameStore>::new().get().tick);
+
+    let dispatch = Dispatch::subscribe_silent(move |store: Rc<GameStore>| {
+        if store.tick == last_tick.get() {
+            return;
+        }
+        last_tick.set(store.tick);
+
+        if let Err(err) = listener.call1(&JsValue::NULL, &tick_event(&store)) {
+            log::warn!("tick listener failed: {:?}", err);
+        }
+    });
+
+    return GameSubscription { _dispatch: dispatch };
+}
+
+fn tick_event(state: &game::State) -> JsValue {
+    let event = serde_json::json!({
+        "tick": state.tick,
+        "money": state.money,
+        "effects": state.effects,
+    });
+    return to_js(event.to_string());
+}
+
+/// JSON parsed into plain JS objects and arrays
+fn to_js(json: String) -> JsValue {
+    return js_sys::JSON::parse(&json).unwrap();
+}
@@ -1,36 +1,134 @@
+#![allow(clippy::needless_return, clippy::single_match)]
+// false positives from the yew 0.19 html! macro
+#![allow(clippy::let_unit_value, clippy::unnecessary_operation)]
+
 use std::rc::Rc;
 
+use gloo_events::EventListener;
+use gloo_file::callbacks::FileReader;
+use web_sys::HtmlInputElement;
 use yew::{prelude::*, virtual_dom::VNode};
 use yewdux::prelude::*;
 
-mod game;
+use zombie_zone_core as game;
+
+#[cfg(feature = "debugger")]
+mod debugger;
+mod js_api;
+mod replays;
+mod runner;
+mod saves;
+mod store;
+
+use store::GameStore;
 
 enum Msg {
     Reset,
     Next,
-    // Start,
-    // Stop,
+    UpgradeTurret { lane: usize, field: usize },
+    SellTurret { lane: usize, field: usize },
+    Load { slot: String },
+    PlayReplay(Rc<game::Replay>),
+    Undo,
+    StepBack { ticks: game::Tick },
+    SetHistoryLength(usize),
+    Start,
+    Stop,
 }
 
+/// how far the step back button rewinds
+const REWIND_TICKS: game::Tick = 10;
+
 fn main() {
     println!("Hello, world!");
     wasm_logger::init(wasm_logger::Config::default());
     yew::start_app::<App>();
 }
 
-impl Reducer<game::State> for Msg {
-    fn apply(&self, mut rc_state: Rc<game::State>) -> Rc<game::State> {
+impl Reducer<GameStore> for Msg {
+    fn apply(&self, mut rc_state: Rc<GameStore>) -> Rc<GameStore> {
         let state = Rc::make_mut(&mut rc_state);
 
         match self {
             Msg::Reset => {
                 log::info!("Msg::Reset");
-                return Rc::new(game::State::new());
+                return Rc::new(GameStore::new(game::State::new()));
+            }
+            Msg::Start => {
+                log::info!("Msg::Start");
+                state.running = true;
+                return rc_state;
+            }
+            Msg::Stop => {
+                log::info!("Msg::Stop");
+                state.running = false;
+                return rc_state;
             }
             Msg::Next => {
                 log::info!("Msg::Next");
                 state.next();
-                return Rc::new(state.clone());
+                if state.tick.is_multiple_of(saves::AUTOSAVE_EVERY_TICKS) {
+                    saves::save(saves::AUTOSAVE_SLOT, state);
+                }
+                return rc_state;
+            }
+            Msg::UpgradeTurret { lane, field } => {
+                log::info!("Msg::UpgradeTurret");
+                if state.playback.is_some() {
+                    log::warn!("upgrade of turret at {}:{} ignored, a replay is playing", lane, field);
+                    return rc_state;
+                }
+                let action = game::Action::UpgradeTurret { player: game::LOCAL_PLAYER, lane: *lane, field: *field };
+                if let Err(err) = state.apply(action) {
+                    log::warn!("upgrade of turret at {}:{} rejected: {:?}", lane, field, err);
+                }
+                return rc_state;
+            }
+            Msg::SellTurret { lane, field } => {
+                log::info!("Msg::SellTurret");
+                if state.playback.is_some() {
+                    log::warn!("sell of turret at {}:{} ignored, a replay is playing", lane, field);
+                    return rc_state;
+                }
+                let action = game::Action::SellTurret { player: game::LOCAL_PLAYER, lane: *lane, field: *field };
+                if let Err(err) = state.apply(action) {
+                    log::warn!("sell of turret at {}:{} rejected: {:?}", lane, field, err);
+                }
+                return rc_state;
+            }
+            Msg::Load { slot } => {
+                log::info!("Msg::Load");
+                match saves::load(slot) {
+                    Some(loaded) => {
+                        let mut store = GameStore::new(loaded);
+                        store.continued = slot == saves::AUTOSAVE_SLOT;
+                        return Rc::new(store);
+                    }
+                    None => return rc_state,
+                }
+            }
+            Msg::PlayReplay(replay) => {
+                log::info!("Msg::PlayReplay");
+                return Rc::new(GameStore::from_replay(replay.clone()));
+            }
+            Msg::Undo => {
+                log::info!("Msg::Undo");
+                if !state.undo() {
+                    log::warn!("nothing left to undo");
+                }
+                return rc_state;
+            }
+            Msg::StepBack { ticks } => {
+                log::info!("Msg::StepBack");
+                if !state.step_back(*ticks) {
+                    log::warn!("nothing left to step back to");
+                }
+                return rc_state;
+            }
+            Msg::SetHistoryLength(length) => {
+                log::info!("Msg::SetHistoryLength");
+                state.set_history_capacity(*length);
+                return rc_state;
             }
         }
     }
@@ -38,15 +136,63 @@ impl Reducer<game::State> for Msg {
 
 #[function_component(App)]
 fn app() -> Html {
-    let (state, dispatch) = use_store::<game::State>();
+    let (state, dispatch) = use_store::<GameStore>();
 
     let reset = dispatch.apply_callback(|_| Msg::Reset);
     let next = dispatch.apply_callback(|_| Msg::Next);
+    let start_stop = if state.running {
+        dispatch.apply_callback(|_| Msg::Stop)
+    } else {
+        dispatch.apply_callback(|_| Msg::Start)
+    };
+    let undo = dispatch.apply_callback(|_| Msg::Undo);
+    let step_back = dispatch.apply_callback(|_| Msg::StepBack { ticks: REWIND_TICKS });
+    let set_history_length = dispatch.apply_callback(|e: Event| {
+        let input = e.target_unchecked_into::<HtmlInputElement>();
+        Msg::SetHistoryLength(input.value().parse().unwrap_or(store::HISTORY_LENGTH))
+    });
+
+    /* the game runs in real time for as long as the runner lives */
+    let tick_interval_ms = state.tick_interval_ms();
+    use_effect_with_deps(
+        move |running| {
+            let runner = if *running { Some(runner::Runner::start(tick_interval_ms)) } else { None };
+            move || drop(runner)
+        },
+        state.running,
+    );
+
+    /* closing the tab saves whatever the last autosave missed, but leaves it alone while nothing was played */
+    use_effect_with_deps(
+        |_| {
+            let listener = EventListener::new(&gloo_utils::window(), "beforeunload", |_| {
+                let store = Dispatch::<GameStore>::new().get();
+                if store.worth_autosaving() {
+                    saves::save(saves::AUTOSAVE_SLOT, &store);
+                }
+            });
+            move || drop(listener)
+        },
+        (),
+    );
+
+    /* offer to continue as long as the fresh game has not been touched */
+    let continue_button = if state.tick == 0 && saves::exists(saves::AUTOSAVE_SLOT) {
+        let resume = dispatch.apply_callback(|_| Msg::Load { slot: saves::AUTOSAVE_SLOT.to_string() });
+        html! {
+            <button onclick={resume} type="button" class="py-[.688rem] px-4 inline-flex justify-center items-center gap-2 rounded-md border-2 border-gray-200 font-semibold text-white hover:text-black hover:bg-white hover:border-white-500 focus:outline-none focus:ring-2 focus:ring-white-500 focus:ring-offset-2 transition-all text-sm dark:border-gray-700 dark:hover:border-white-500">
+            {"Continue"}
+            </button>
+        }
+    } else {
+        html! {}
+    };
 
     html! {
         <>
             <h1>{"Welcome to the Zombie Zone"}</h1>
             <div>{"tick: "}{state.tick}</div>
+            <div>{"money: "}{state.money}</div>
             <Grid />
             <div class="">
                 <button onclick={reset} type="button" class="py-[.688rem] px-4 inline-flex justify-center items-center gap-2 rounded-md border-2 border-gray-200 font-semibold text-white hover:text-black hover:bg-white hover:border-white-500 focus:outline-none focus:ring-2 focus:ring-white-500 focus:ring-offset-2 transition-all text-sm dark:border-gray-700 dark:hover:border-white-500">
@@ -55,14 +201,45 @@ fn app() -> Html {
                 <button onclick={next} type="button" class="py-[.688rem] px-4 inline-flex justify-center items-center gap-2 rounded-md border-2 border-gray-200 font-semibold text-white hover:text-black hover:bg-white hover:border-white-500 focus:outline-none focus:ring-2 focus:ring-white-500 focus:ring-offset-2 transition-all text-sm dark:border-gray-700 dark:hover:border-white-500">
                 {"Next"}
                 </button>
+                <button onclick={start_stop} type="button" class="py-[.688rem] px-4 inline-flex justify-center items-center gap-2 rounded-md border-2 border-gray-200 font-semibold text-white hover:text-black hover:bg-white hover:border-white-500 focus:outline-none focus:ring-2 focus:ring-white-500 focus:ring-offset-2 transition-all text-sm dark:border-gray-700 dark:hover:border-white-500">
+                {if state.running { "Stop" } else { "Start" }}
+                </button>
+                <button onclick={undo} disabled={state.history.borrow().is_empty()} type="button" class="py-[.688rem] px-4 inline-flex justify-center items-center gap-2 rounded-md border-2 border-gray-200 font-semibold text-white hover:text-black hover:bg-white hover:border-white-500 focus:outline-none focus:ring-2 focus:ring-white-500 focus:ring-offset-2 transition-all text-sm dark:border-gray-700 dark:hover:border-white-500 disabled:opacity-50">
+                {"Undo"}
+                </button>
+                <button onclick={step_back} disabled={state.history.borrow().is_empty()} type="button" class="py-[.688rem] px-4 inline-flex justify-center items-center gap-2 rounded-md border-2 border-gray-200 font-semibold text-white hover:text-black hover:bg-white hover:border-white-500 focus:outline-none focus:ring-2 focus:ring-white-500 focus:ring-offset-2 transition-all text-sm dark:border-gray-700 dark:hover:border-white-500 disabled:opacity-50">
+                {"Back "}{REWIND_TICKS}{" ticks"}
+                </button>
+                {continue_button}
             </div>
+            <div class="flex flex-row gap-2 items-center">
+                <label for="history-length">{"undo history"}</label>
+                <input id="history-length" type="number" min="0" onchange={set_history_length} value={state.history.borrow().capacity().to_string()} class="w-20 py-1 px-2 rounded-md text-black" />
+                <div>{"("}{state.history.borrow().len()}{" kept)"}</div>
+            </div>
+            <SaveSlots />
+            <ReplayControls />
+            {debugger_panel()}
         </>
     }
 }
 
+/// the css class a glyph style is drawn with
+fn glyph_class(style: game::GlyphStyle) -> &'static str {
+    return match style {
+        game::GlyphStyle::Plain => "",
+        game::GlyphStyle::Alert => "text-red-500",
+        game::GlyphStyle::Muted => "text-gray-400",
+        game::GlyphStyle::Cold => "text-blue-300",
+        game::GlyphStyle::Faded => "opacity-40",
+    };
+}
+
 #[function_component(Grid)]
 fn grid() -> Html {
-    let (state, _) = use_store::<game::State>();
+    let (state, _) = use_store::<GameStore>();
+    let (frame, _) = use_store::<runner::FrameStore>();
+    let menu = use_state(|| None::<(usize, usize)>);
 
     let mut lanes: Vec<VNode> = vec![];
     for i in 0..state.grid.len() {
@@ -70,26 +247,64 @@ fn grid() -> Html {
 
         match &state.grid[i] {
             Some(lane) => {
-                for i in 0..lane.len() {
-                    let game::Field(field) = lane[i].clone();
+                for j in 0..lane.len() {
+                    let game::Field(field) = lane[j].clone();
+
+                    /* effects are drawn on top of whatever the field holds */
+                    let overlay = state
+                        .effects_at(i, j)
+                        .map(|effect| {
+                            let glyph = effect.kind.glyph();
+                            html! { <span class="absolute inset-0 text-yellow-300">{glyph}</span> }
+                        })
+                        .collect::<Html>();
 
                     if field.is_empty() {
-                        fields.push(html! { <div>{"_"}</div> });
+                        fields.push(html! { <div class="relative">{"_"}{overlay}</div> });
                         continue;
                     }
 
-                    match field[0] {
-                        game::Entity::Zombie(_) => {
-                            fields.push(html! {<div>{"Z"}</div>});
-                        }
-                        game::Entity::Turret => {
-                            fields.push(html! {<div>{"T"}</div>});
+                    /* a stack shows each kind of entity once, with a badge counting it */
+                    let mut glyphs: Vec<((&str, &str, i8), usize)> = vec![];
+                    for entity in field.iter() {
+                        let behavior = entity.behavior();
+                        let glyph = behavior.glyph();
+                        let glyph = (glyph.symbol, glyph_class(glyph.style), behavior.heading());
+
+                        match glyphs.iter_mut().find(|(g, _)| *g == glyph) {
+                            Some((_, count)) => *count += 1,
+                            None => glyphs.push((glyph, 1)),
                         }
-                        game::Entity::Bullet(_) => {
-                            fields.push(html! {<div>{"°"}</div>});
+                    }
+
+                    let stack = glyphs
+                        .into_iter()
+                        .map(|((glyph, class, drift), count)| {
+                            let badge = if count > 1 {
+                                html! { <sup class="text-xs">{count}</sup> }
+                            } else {
+                                html! {}
+                            };
+                            /* while running, moving entities slide towards the field they enter next */
+                            let offset = drift as f64 * frame.interpolation;
+                            let style = format!("transform: translateX(calc({} * (100% + 0.5rem)))", offset);
+                            html! { <span class={classes!("inline-block", class)} {style}>{glyph}{badge}</span> }
+                        })
+                        .collect::<Html>();
+
+                    match state.turret_at(i, j) {
+                        Some(turret) => {
+                            let menu = menu.clone();
+                            let oncontextmenu = Callback::from(move |e: MouseEvent| {
+                                e.prevent_default();
+                                menu.set(Some((i, j)));
+                            });
+                            let title = format!("{:?} tier {}", turret.kind, turret.tier + 1);
+
+                            fields.push(html! {<div {oncontextmenu} {title} class="relative cursor-context-menu">{stack}{overlay}</div>});
                         }
-                        game::Entity::Collision => {
-                            fields.push(html! {<div>{"x"}</div>});
+                        None => {
+                            fields.push(html! {<div class="relative">{stack}{overlay}</div>});
                         }
                     }
                 }
@@ -113,9 +328,175 @@ fn grid() -> Html {
 
     let lanes_as_html = lanes.into_iter().collect::<Html>();
 
+    let turret_menu = match *menu {
+        Some((lane, field)) => {
+            let menu = menu.clone();
+            let on_close = Callback::from(move |_| menu.set(None));
+            html! { <TurretMenu lane={lane} field={field} on_close={on_close} /> }
+        }
+        None => html! {},
+    };
+
+    html! {
+        <>
+            <div class="text-3xl">
+                {lanes_as_html}
+            </div>
+            {turret_menu}
+        </>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct TurretMenuProps {
+    lane: usize,
+    field: usize,
+    on_close: Callback<()>,
+}
+
+#[function_component(TurretMenu)]
+fn turret_menu(props: &TurretMenuProps) -> Html {
+    let (state, dispatch) = use_store::<GameStore>();
+    let (lane, field) = (props.lane, props.field);
+
+    let turret = match state.turret_at(lane, field) {
+        Some(turret) => turret,
+        None => return html! {},
+    };
+
+    let close = props.on_close.reform(|_: MouseEvent| ());
+    let upgrade = dispatch.apply_callback(move |_: MouseEvent| Msg::UpgradeTurret { lane, field });
+    let on_close = props.on_close.clone();
+    let sell = dispatch.apply_callback(move |_: MouseEvent| {
+        on_close.emit(());
+        Msg::SellTurret { lane, field }
+    });
+
+    let upgrade_button = match turret.upgrade_cost() {
+        Some(cost) => html! {
+            <button onclick={upgrade} disabled={cost > state.money} type="button" class="py-1 px-2 rounded-md border-2 border-gray-200 disabled:opacity-50">
+                {"Upgrade ("}{cost}{")"}
+            </button>
+        },
+        None => html! { <span>{"max tier"}</span> },
+    };
+
+    html! {
+        <div class="flex flex-row gap-2 items-center">
+            <div>{"Turret lane "}{lane + 1}{", field "}{field + 1}{", tier "}{turret.tier + 1}{":"}</div>
+            {upgrade_button}
+            <button onclick={sell} type="button" class="py-1 px-2 rounded-md border-2 border-gray-200">
+                {"Sell (+"}{turret.refund()}{")"}
+            </button>
+            <button onclick={close} type="button" class="py-1 px-2 rounded-md border-2 border-gray-200">
+                {"Close"}
+            </button>
+        </div>
+    }
+}
+
+#[cfg(feature = "debugger")]
+fn debugger_panel() -> Html {
+    return html! { <debugger::Debugger /> };
+}
+
+#[cfg(not(feature = "debugger"))]
+fn debugger_panel() -> Html {
+    return html! {};
+}
+
+#[function_component(SaveSlots)]
+fn save_slots() -> Html {
+    let (state, dispatch) = use_store::<GameStore>();
+    let slots = use_state(saves::slots);
+    let name = use_state(String::new);
+
+    let oninput = {
+        let name = name.clone();
+        Callback::from(move |e: InputEvent| name.set(e.target_unchecked_into::<HtmlInputElement>().value()))
+    };
+    let save = {
+        let (slots, name) = (slots.clone(), name.clone());
+        Callback::from(move |_: MouseEvent| {
+            saves::save(&name, &state);
+            slots.set(saves::slots());
+        })
+    };
+
+    let rows = slots
+        .iter()
+        .map(|slot| {
+            let load = {
+                let slot = slot.clone();
+                dispatch.apply_callback(move |_: MouseEvent| Msg::Load { slot: slot.clone() })
+            };
+            let delete = {
+                let (slots, slot) = (slots.clone(), slot.clone());
+                Callback::from(move |_: MouseEvent| {
+                    saves::delete(&slot);
+                    slots.set(saves::slots());
+                })
+            };
+
+            html! {
+                <div class="flex flex-row gap-2 items-center">
+                    <div>{slot}</div>
+                    <button onclick={load} type="button" class="py-1 px-2 rounded-md border-2 border-gray-200">{"Load"}</button>
+                    <button onclick={delete} type="button" class="py-1 px-2 rounded-md border-2 border-gray-200">{"Delete"}</button>
+                </div>
+            }
+        })
+        .collect::<Html>();
+
+    html! {
+        <div class="flex flex-col gap-2">
+            <div class="flex flex-row gap-2 items-center">
+                <input {oninput} value={(*name).clone()} placeholder="save name" class="py-1 px-2 rounded-md text-black" />
+                <button onclick={save} disabled={name.is_empty()} type="button" class="py-1 px-2 rounded-md border-2 border-gray-200 disabled:opacity-50">
+                    {"Save"}
+                </button>
+            </div>
+            {rows}
+        </div>
+    }
+}
+
+#[function_component(ReplayControls)]
+fn replay_controls() -> Html {
+    let (state, dispatch) = use_store::<GameStore>();
+    /* dropping the reader would cancel the read */
+    let reader = use_mut_ref(|| None::<FileReader>);
+
+    let download = {
+        let state = state.clone();
+        Callback::from(move |_: MouseEvent| replays::download(&state.recording.borrow()))
+    };
+    let onchange = Callback::from(move |e: Event| {
+        let input = e.target_unchecked_into::<HtmlInputElement>();
+        let file = match input.files().and_then(|files| files.get(0)) {
+            Some(file) => file,
+            None => return,
+        };
+
+        let dispatch = dispatch.clone();
+        *reader.borrow_mut() = Some(replays::read(file, move |replay| dispatch.apply(Msg::PlayReplay(Rc::new(replay)))));
+    });
+
+    let status = match &state.playback {
+        Some(replay) => html! { <div>{"playing replay, tick "}{state.tick}{" of "}{replay.tick()}</div> },
+        None => html! {},
+    };
+
     html! {
-        <div class="text-3xl">
-            {lanes_as_html}
+        <div class="flex flex-row gap-2 items-center">
+            <button onclick={download} type="button" class="py-1 px-2 rounded-md border-2 border-gray-200">
+                {"Download replay"}
+            </button>
+            <label class="py-1 px-2 rounded-md border-2 border-gray-200 cursor-pointer">
+                {"Play replay"}
+                <input {onchange} type="file" accept=".json,application/json" class="hidden" />
+            </label>
+            {status}
         </div>
     }
 }
@@ -0,0 +1,40 @@
+use gloo_file::{callbacks::FileReader, Blob, File, ObjectUrl};
+use wasm_bindgen::JsCast;
+use web_sys::HtmlElement;
+
+use crate::game;
+
+/// offers the replay as a file download
+pub fn download(replay: &game::Replay) {
+    let blob = Blob::new_with_options(replay.to_json().as_str(), Some("application/json"));
+    let url = ObjectUrl::from(blob);
+
+    let link = gloo_utils::document().create_element("a").unwrap();
+    let _ = link.set_attribute("href", &url);
+    let _ = link.set_attribute("download", &format!("zombie-zone-replay-{}.json", replay.tick()));
+    match link.dyn_ref::<HtmlElement>() {
+        Some(link) => link.click(),
+        None => {}
+    }
+}
+
+/// reads a replay file, `on_load` only gets called for a usable replay
+pub fn read(file: web_sys::File, on_load: impl FnOnce(game::Replay) + 'static) -> FileReader {
+    let file = File::from(file);
+    let name = file.name();
+
+    return gloo_file::callbacks::read_as_text(&Blob::from(file), move |result| {
+        let json = match result {
+            Ok(json) => json,
+            Err(err) => {
+                log::warn!("reading replay {} failed: {}", name, err);
+                return;
+            }
+        };
+
+        match game::Replay::from_json(&json) {
+            Ok(replay) => on_load(replay),
+            Err(err) => log::warn!("replay {} is unusable: {:?}", name, err),
+        }
+    });
+}
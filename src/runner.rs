@@ -0,0 +1,68 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gloo_render::{request_animation_frame, AnimationFrame};
+use yewdux::prelude::*;
+
+use crate::{game, store::GameStore, Msg};
+
+/// the browser's high resolution time
+pub struct PerformanceClock(web_sys::Performance);
+
+impl PerformanceClock {
+    pub fn new() -> PerformanceClock {
+        return PerformanceClock(gloo_utils::window().performance().unwrap());
+    }
+}
+
+impl game::Clock for PerformanceClock {
+    fn now_ms(&self) -> f64 {
+        return self.0.now();
+    }
+}
+
+/// how far the running game is between two ticks, see `Scheduler::interpolation`
+#[derive(Default, Clone, PartialEq, Store)]
+pub struct FrameStore {
+    pub interpolation: f64,
+}
+
+/// Runs the game in real time until dropped. Every animation frame asks the
+/// scheduler how many ticks are due and applies them before the frame is drawn.
+pub struct Runner {
+    frame: Rc<RefCell<Option<AnimationFrame>>>,
+}
+
+impl Runner {
+    pub fn start(tick_interval_ms: u16) -> Runner {
+        let scheduler = game::Scheduler::new(PerformanceClock::new(), tick_interval_ms);
+        let frame = Rc::new(RefCell::new(None));
+        Runner::request_frame(Rc::new(RefCell::new(scheduler)), frame.clone());
+        return Runner { frame };
+    }
+
+    fn request_frame(
+        scheduler: Rc<RefCell<game::Scheduler<PerformanceClock>>>,
+        frame: Rc<RefCell<Option<AnimationFrame>>>,
+    ) {
+        let next_frame = frame.clone();
+        *frame.borrow_mut() = Some(request_animation_frame(move |_| {
+            let ticks = scheduler.borrow_mut().frame();
+            for _ in 0..ticks {
+                Dispatch::<GameStore>::new().apply(Msg::Next);
+            }
+            let interpolation = scheduler.borrow().interpolation();
+            Dispatch::<FrameStore>::new().set(FrameStore { interpolation });
+
+            Runner::request_frame(scheduler, next_frame);
+        }));
+    }
+}
+
+impl Drop for Runner {
+    fn drop(&mut self) {
+        /* cancels the pending frame, which also ends the chain of frames */
+        self.frame.borrow_mut().take();
+        Dispatch::<FrameStore>::new().set(FrameStore::default());
+    }
+}
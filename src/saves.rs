@@ -0,0 +1,60 @@
+use gloo_storage::{LocalStorage, Storage};
+
+use crate::game;
+
+/// every save slot is its own localStorage entry under this prefix
+const KEY_PREFIX: &str = "zombie-zone/save/";
+/// the slot written automatically while playing and when the tab closes
+pub const AUTOSAVE_SLOT: &str = "autosave";
+pub const AUTOSAVE_EVERY_TICKS: u32 = 5;
+
+fn key(slot: &str) -> String {
+    return format!("{}{}", KEY_PREFIX, slot);
+}
+
+pub fn save(slot: &str, state: &game::State) {
+    if let Err(err) = LocalStorage::set(key(slot), game::SavedState::new(state.clone())) {
+        log::warn!("saving to slot {} failed: {}", slot, err);
+    }
+}
+
+/// the state saved in the slot, migrated to the current schema
+pub fn load(slot: &str) -> Option<game::State> {
+    let value = match LocalStorage::get::<serde_json::Value>(key(slot)) {
+        Ok(value) => value,
+        Err(_) => return None,
+    };
+
+    match game::SavedState::from_value(value) {
+        Ok(saved) => return Some(saved.state),
+        Err(err) => {
+            log::warn!("save in slot {} is unusable: {:?}", slot, err);
+            return None;
+        }
+    }
+}
+
+pub fn delete(slot: &str) {
+    LocalStorage::delete(key(slot));
+}
+
+pub fn exists(slot: &str) -> bool {
+    return matches!(LocalStorage::raw().get_item(&key(slot)), Ok(Some(_)));
+}
+
+/// names of all slots holding a save, sorted
+pub fn slots() -> Vec<String> {
+    let storage = LocalStorage::raw();
+    let mut slots = vec![];
+    for i in 0..LocalStorage::length() {
+        match storage.key(i) {
+            Ok(Some(key)) => match key.strip_prefix(KEY_PREFIX) {
+                Some(slot) => slots.push(slot.to_string()),
+                None => {}
+            },
+            _ => {}
+        }
+    }
+    slots.sort();
+    return slots;
+}
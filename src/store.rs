@@ -0,0 +1,204 @@
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+
+use yewdux::prelude::*;
+
+use crate::game;
+
+/// how many earlier states undo can go back to, unless the player changes it
+pub const HISTORY_LENGTH: usize = 100;
+
+/// The core game state, held in a yewdux store. The recording and the
+/// history grow with the session, they are shared between the clones of the
+/// store and updated in place, so cloning the store in the reducer costs no
+/// more than cloning the state.
+#[derive(Clone)]
+pub struct GameStore {
+    pub state: game::State,
+    /// the session so far, since the last reset or load
+    pub recording: Rc<RefCell<game::Replay>>,
+    /// a loaded replay that drives the game instead of the player
+    pub playback: Option<Rc<game::Replay>>,
+    /// the first tick the playback differed from its recording at, later ticks differ as a consequence
+    pub diverged: Option<game::Tick>,
+    /// the states before each tick and action, for undo
+    pub history: Rc<RefCell<game::History<Snapshot>>>,
+    /// ticks come from the clock instead of the next button
+    pub running: bool,
+    /// the session continues the autosave, so writing it back loses nothing
+    pub continued: bool,
+    /// counts the changes to the recording and the history, the shared
+    /// copies cannot tell an older store from a newer one
+    revision: u64,
+}
+
+/// a state undo can go back to
+#[derive(Clone)]
+pub struct Snapshot {
+    pub state: game::State,
+    /// how many actions the recording held at that state
+    pub actions: usize,
+}
+
+impl GameStore {
+    pub fn new(state: game::State) -> GameStore {
+        return GameStore {
+            recording: Rc::new(RefCell::new(game::Replay::new(state.clone()))),
+            state,
+            playback: None,
+            diverged: None,
+            history: Rc::new(RefCell::new(game::History::new(HISTORY_LENGTH))),
+            running: false,
+            continued: false,
+            revision: 0,
+        };
+    }
+
+    pub fn from_replay(replay: Rc<game::Replay>) -> GameStore {
+        return GameStore {
+            state: replay.start.clone(),
+            recording: Rc::new(RefCell::new((*replay).clone())),
+            playback: Some(replay),
+            diverged: None,
+            history: Rc::new(RefCell::new(game::History::new(HISTORY_LENGTH))),
+            running: false,
+            continued: false,
+            revision: 0,
+        };
+    }
+
+    /// whether closing the tab should write the autosave, a fresh or just
+    /// reset game would only overwrite it with tick 0
+    pub fn worth_autosaving(&self) -> bool {
+        return self.continued || self.state.tick > 0 || !self.recording.borrow().actions.is_empty();
+    }
+
+    /// advances the game, following the replay while one plays
+    pub fn next(&mut self) {
+        self.remember();
+        match self.playback.clone() {
+            Some(replay) => {
+                replay.step(&mut self.state);
+                let played = (self.state.tick - replay.start.tick) as usize;
+                match replay.hashes.get(played - 1) {
+                    Some(hash) if self.diverged.is_none() && *hash != self.state.hash() => {
+                        log::warn!("replay diverged from its recording at tick {}", self.state.tick);
+                        self.diverged = Some(self.state.tick);
+                    }
+                    _ => {}
+                }
+                /* the recording already holds the whole replay, the player takes over */
+                if self.state.tick >= replay.tick() {
+                    self.playback = None;
+                }
+            }
+            None => {
+                self.state.next();
+                self.recording.borrow_mut().record_next(&self.state);
+            }
+        }
+    }
+
+    /// applies and records a player action
+    pub fn apply(&mut self, action: game::Action) -> Result<game::Money, game::ActionError> {
+        self.remember();
+        let result = self.state.apply(&action);
+        self.recording.borrow_mut().record_action(action);
+        return result;
+    }
+
+    /// shrinking drops the oldest states that no longer fit
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        self.history.borrow_mut().set_capacity(capacity);
+        self.revision += 1;
+    }
+
+    fn remember(&mut self) {
+        let actions = self.recording.borrow().actions.len();
+        self.history.borrow_mut().push(Rc::new(Snapshot { state: self.state.clone(), actions }));
+        self.revision += 1;
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        self.state = snapshot.state.clone();
+        /* a playing replay stays whole, it replays the undone ticks again */
+        if self.playback.is_none() {
+            let mut recording = self.recording.borrow_mut();
+            let ticks = self.state.tick - recording.start.tick;
+            recording.truncate(ticks, snapshot.actions);
+        }
+        self.revision += 1;
+    }
+
+    /// reverts the last tick or action, false once the history is used up
+    pub fn undo(&mut self) -> bool {
+        let popped = self.history.borrow_mut().pop();
+        match popped {
+            Some(snapshot) => {
+                self.restore(&snapshot);
+                return true;
+            }
+            None => return false,
+        }
+    }
+
+    /// rewinds by `ticks` ticks, or as far as the history goes
+    pub fn step_back(&mut self, ticks: game::Tick) -> bool {
+        let target = self.state.tick.saturating_sub(ticks);
+        let popped = self.history.borrow_mut().pop_until(|snapshot| snapshot.state.tick <= target);
+        match popped {
+            Some(snapshot) => {
+                self.restore(&snapshot);
+                return true;
+            }
+            None => return false,
+        }
+    }
+}
+
+
+/// The shared recording and history are compared by identity and revision,
+/// comparing them by value would take as long as the session.
+impl PartialEq for GameStore {
+    fn eq(&self, other: &Self) -> bool {
+        return self.state == other.state
+            && Rc::ptr_eq(&self.recording, &other.recording)
+            && self.playback.as_ref().map(Rc::as_ptr) == other.playback.as_ref().map(Rc::as_ptr)
+            && self.diverged == other.diverged
+            && Rc::ptr_eq(&self.history, &other.history)
+            && self.running == other.running
+            && self.continued == other.continued
+            && self.revision == other.revision;
+    }
+}
+
+impl Default for GameStore {
+    fn default() -> Self {
+        return GameStore::new(game::State::default());
+    }
+}
+
+impl Store for GameStore {
+    fn new() -> Self {
+        return GameStore::default();
+    }
+
+    fn should_notify(&self, old: &Self) -> bool {
+        return self != old;
+    }
+}
+
+impl Deref for GameStore {
+    type Target = game::State;
+
+    fn deref(&self) -> &Self::Target {
+        &self.state
+    }
+}
+
+impl DerefMut for GameStore {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.state
+    }
+}